@@ -1,14 +1,24 @@
 use log::*;
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::hash::Hash;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CellState {
     Uncovered,
     Covered,
     Flagged,
+    Questioned,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GameCell {
     pub state: CellState,
     pub mine: bool,
@@ -40,6 +50,7 @@ impl std::fmt::Display for GameCell {
             match self.state {
                 CellState::Covered => "C",
                 CellState::Flagged => "F",
+                CellState::Questioned => "Q",
                 CellState::Uncovered => "U",
             },
             if self.mine { "X" } else { "O" }
@@ -47,239 +58,1439 @@ impl std::fmt::Display for GameCell {
     }
 }
 
+/// Everything there is to know about a single cell
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CellInfo {
+    pub state: CellState,
+    pub mine: bool,
+    pub adjacent: usize,
+    pub neighbors: Vec<(u8, u8)>,
+}
+
+impl std::fmt::Display for CellInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}, mine: {}, adjacent mines: {}",
+            self.state, self.mine, self.adjacent
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GameState {
     Won,
     Continue,
     Lost,
 }
 
-#[derive(Debug)]
+/// Cells the uncovered numbers decide on their own, see [Game::analyze]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Analysis {
+    /// Covered cells which must be mines
+    pub forced_mines: Vec<(u8, u8)>,
+    /// Covered cells which must be safe
+    pub forced_safes: Vec<(u8, u8)>,
+}
+
+/// Parameters a board was generated with
+///
+/// Used to generate new boards of the same difficulty.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct GameParams {
+    pub width: u8,
+    pub height: u8,
+    pub mines: usize,
+}
+
+/// Board presets offered in the menu
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum DifficultyLevel {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl DifficultyLevel {
+    /// Dimensions and mine count of the preset: roughly a quarter
+    /// of the cells are mines
+    pub fn params(self) -> GameParams {
+        let (size, mines) = match self {
+            DifficultyLevel::Easy => (5, 6),
+            DifficultyLevel::Medium => (10, 25),
+            DifficultyLevel::Hard => (15, 56),
+        };
+
+        GameParams {
+            width: size,
+            height: size,
+            mines,
+        }
+    }
+}
+
+/// Places mines on a fresh board
+pub trait BoardGenerator {
+    /// Returns `w * h` covered cells, laid out row by row
+    fn generate(&self, w: u8, h: u8, mines: usize) -> Vec<GameCell>;
+}
+
+/// Places exactly `mines` mines uniformly at random
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RandomGenerator;
+
+impl BoardGenerator for RandomGenerator {
+    fn generate(&self, w: u8, h: u8, mines: usize) -> Vec<GameCell> {
+        sample_mines(&mut thread_rng(), w, h, mines)
+    }
+}
+
+/// Places exactly `mines` mines, the same way every time for the same seed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SeededGenerator {
+    pub seed: u64,
+}
+
+impl BoardGenerator for SeededGenerator {
+    fn generate(&self, w: u8, h: u8, mines: usize) -> Vec<GameCell> {
+        sample_mines(&mut StdRng::seed_from_u64(self.seed), w, h, mines)
+    }
+}
+
+fn sample_mines(rng: &mut impl Rng, w: u8, h: u8, mines: usize) -> Vec<GameCell> {
+    let mut cells = vec![GameCell::default(); h as usize * w as usize];
+    let len = cells.len();
+    // Leave at least one safe cell
+    for idx in sample(rng, len, mines.min(len.saturating_sub(1))) {
+        cells[idx].mine = true;
+    }
+    cells
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SymmetryKind {
+    /// Mirrored around the vertical axis: left matches right
+    Horizontal,
+    /// Mirrored around the horizontal axis: top matches bottom
+    Vertical,
+    /// Rotated by 180 degrees around the center
+    Rotational,
+}
+
+impl SymmetryKind {
+    /// Returns the coordinates matching `(x, y)` on a `w` by `h` board
+    pub fn mirror(self, w: u8, h: u8, x: u8, y: u8) -> (u8, u8) {
+        match self {
+            SymmetryKind::Horizontal => (w - 1 - x, y),
+            SymmetryKind::Vertical => (x, h - 1 - y),
+            SymmetryKind::Rotational => (w - 1 - x, h - 1 - y),
+        }
+    }
+}
+
+/// Places mines symmetrically
+///
+/// Mines are seeded on one half of the board and mirrored onto the other
+/// one, so the board ends up with at most `mines` mines: an odd count can
+/// only be reached through a cell that is its own mirror.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SymmetryGenerator {
+    pub kind: SymmetryKind,
+}
+
+impl BoardGenerator for SymmetryGenerator {
+    fn generate(&self, w: u8, h: u8, mines: usize) -> Vec<GameCell> {
+        let mut cells = vec![GameCell::default(); h as usize * w as usize];
+        // Leave at least one safe cell
+        let mines = mines.min(cells.len().saturating_sub(1));
+
+        // Pairs of mirrored cells, with the cells on the axis paired with themselves
+        let mut pairs = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                let idx = y as usize * w as usize + x as usize;
+                let (mx, my) = self.kind.mirror(w, h, x, y);
+                let mirrored = my as usize * w as usize + mx as usize;
+                if idx <= mirrored {
+                    pairs.push((idx, mirrored));
+                }
+            }
+        }
+        pairs.shuffle(&mut thread_rng());
+
+        let mut placed = 0;
+        for (idx, mirrored) in pairs {
+            let count = if idx == mirrored { 1 } else { 2 };
+            if placed + count <= mines {
+                cells[idx].mine = true;
+                cells[mirrored].mine = true;
+                placed += count;
+            }
+        }
+        cells
+    }
+}
+
+/// A single player action, see [Game::apply_move]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Move {
+    /// See [Game::open]
+    Open(u8, u8),
+    /// See [Game::flag]
+    Flag(u8, u8),
+    /// See [Game::chord]
+    Chord(u8, u8),
+}
+
+/// Outcome of [Game::apply_move]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MoveResult {
+    /// Nothing changed: the move wasn't possible or the game is already over
+    Ignored,
+    /// The board changed and the game goes on
+    Changed,
+    /// The move ended the game with the given state
+    Over(GameState),
+}
+
+impl MoveResult {
+    /// Checks whether the move changed the board
+    pub fn changed(self) -> bool {
+        self != MoveResult::Ignored
+    }
+}
+
+/// What it takes to win a [Game]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WinCondition {
+    /// Every cell without a mine is uncovered
+    OpenAllSafe,
+    /// Every mine is flagged and nothing else is
+    FlagAllMines,
+}
+
+/// Saves made before [WinCondition]s existed are classic games
+#[cfg(feature = "serde")]
+fn classic_win_condition() -> WinCondition {
+    WinCondition::OpenAllSafe
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Game {
     h: u8,
     w: u8,
     cells: Vec<GameCell>,
     state: GameState,
+    /// Whether flagging cycles through [CellState::Questioned]
+    question_marks: bool,
+    #[cfg_attr(feature = "serde", serde(default = "classic_win_condition"))]
+    win_condition: WinCondition,
+    /// Whether the first mine opened gets moved away instead of losing
+    #[cfg_attr(feature = "serde", serde(default))]
+    mulligan: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    mulligan_used: bool,
+    /// Whether a cell was opened yet, the first one never has a mine
+    #[cfg_attr(feature = "serde", serde(default))]
+    opened: bool,
+    /// Whether any cell was opened or flagged yet, see [Game::has_started]
+    #[cfg_attr(feature = "serde", serde(default))]
+    started: bool,
+    /// Moves made so far, see [Game::apply_move]
+    #[cfg_attr(feature = "serde", serde(default))]
+    history: Vec<Move>,
 }
 
-impl Game {
-    pub fn new(w: u8, h: u8) -> Self {
-        Self {
-            h,
-            w,
-            cells: (0..h * w).into_iter().map(|_| GameCell::new()).collect(),
-            state: GameState::Continue,
+/// Version of the [SavedGame] format, bumped on every incompatible change
+pub const SAVE_VERSION: u32 = 1;
+
+/// Serialized form of a [Game] (see [Game::to_json])
+#[cfg(feature = "serde")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub game: Game,
+}
+
+/// Reasons a saved game can't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// Not a saved game at all
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    /// Saved by an unknown version of the game
+    UnsupportedVersion(u32),
+    /// The cells don't match the board dimensions
+    InvalidBoard,
+    /// Not a share code (see [decode_share])
+    Encoding(base64::DecodeError),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "serde")]
+            LoadError::Json(err) => write!(f, "malformed saved game: {}", err),
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported saved game version {}, expected {}",
+                version, SAVE_VERSION
+            ),
+            LoadError::InvalidBoard => write!(f, "saved board doesn't match its dimensions"),
+            LoadError::Encoding(err) => write!(f, "malformed share code: {}", err),
         }
     }
+}
 
-    pub fn height(&self) -> u8 {
-        self.h
-    }
+impl std::error::Error for LoadError {}
 
-    pub fn width(&self) -> u8 {
-        self.w
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Json(err)
     }
+}
 
-    pub fn state(&self) -> GameState {
-        self.state
+impl From<base64::DecodeError> for LoadError {
+    fn from(err: base64::DecodeError) -> Self {
+        LoadError::Encoding(err)
     }
+}
 
-    pub fn cell_state(&self, x: u8, y: u8) -> Option<CellState> {
-        self.cell(x, y).map(|cell| cell.state)
+/// Version of the share code format, bumped on every incompatible change
+const SHARE_VERSION: u8 = 2;
+/// Version of the [Game::to_bytes] format, bumped on every incompatible change
+const BYTES_VERSION: u8 = 2;
+
+/// Encodes the board layout and the moves made on it into a short URL safe code
+///
+/// The progress itself isn't kept, `game` should be the board before the
+/// first move and `moves` replay the game from there. The code is the
+/// version, the width, the height, a bit per cell and three bytes per move
+/// (the kind and the coordinates), in base64.
+pub fn encode_share(game: &Game, moves: &[Move]) -> String {
+    let mut bytes = vec![SHARE_VERSION, game.w, game.h];
+    bytes.resize(3 + bitset_bytes(game.cells.len()), 0);
+    for (idx, cell) in game.cells.iter().enumerate() {
+        if cell.mine {
+            bytes[3 + idx / 8] |= 1 << (idx % 8);
+        }
     }
+    bytes.extend(moves_to_bytes(moves));
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
 
-    pub fn has_mine(&self, x: u8, y: u8) -> Option<bool> {
-        self.cell(x, y).map(|cell| cell.mine)
-    }
+/// 64-bit FNV-1a hash of the bytes
+///
+/// Unlike the standard library's hashers its output is specified, so it
+/// stays the same across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
 
-    pub fn adjacent_mines(&self, x: u8, y: u8) -> Option<usize> {
-        self.cell(x, y).and_then(|_| {
-            let mines = self
-                .adj(x, y)
-                .into_iter()
-                .filter(|(x, y)| self.cells[(y * self.w + x) as usize].mine)
-                .count();
+/// Number of bytes needed to pack the given number of bits
+fn bitset_bytes(bits: usize) -> usize {
+    bits / 8 + (bits % 8).min(1)
+}
 
-            Some(mines)
+/// Packs the moves into three bytes each: the kind and the coordinates
+fn moves_to_bytes(moves: &[Move]) -> Vec<u8> {
+    moves
+        .iter()
+        .flat_map(|m| match *m {
+            Move::Open(x, y) => [0, x, y],
+            Move::Flag(x, y) => [1, x, y],
+            Move::Chord(x, y) => [2, x, y],
         })
+        .collect()
+}
+
+/// Unpacks moves packed with [moves_to_bytes] for a board of the given size
+///
+/// Returns `None` if the bytes are truncated or a move is unknown or off
+/// the board.
+fn moves_from_bytes(bytes: &[u8], w: u8, h: u8) -> Option<Vec<Move>> {
+    let chunks = bytes.chunks_exact(3);
+    if !chunks.remainder().is_empty() {
+        return None;
     }
+    chunks
+        .map(|chunk| match *chunk {
+            [_, x, y] if x >= w || y >= h => None,
+            [0, x, y] => Some(Move::Open(x, y)),
+            [1, x, y] => Some(Move::Flag(x, y)),
+            [2, x, y] => Some(Move::Chord(x, y)),
+            _ => None,
+        })
+        .collect()
+}
 
-    pub fn mines(&self) -> usize {
-        self.cells.iter().filter(|c| c.mine).count()
+/// Decodes a board and its moves encoded with [encode_share]
+pub fn decode_share(code: &str) -> Result<(Game, Vec<Move>), LoadError> {
+    let bytes = base64::decode_config(code, base64::URL_SAFE_NO_PAD)?;
+    let (version, w, h, rest) = match bytes.as_slice() {
+        [version, w, h, rest @ ..] => (*version, *w, *h, rest),
+        _ => return Err(LoadError::InvalidBoard),
+    };
+    if version != SHARE_VERSION {
+        return Err(LoadError::UnsupportedVersion(version as u32));
     }
 
-    pub fn flagged(&self) -> usize {
-        self.cells
-            .iter()
-            .filter(|c| c.state == CellState::Flagged)
-            .count()
+    let len = w as usize * h as usize;
+    if rest.len() < bitset_bytes(len) {
+        return Err(LoadError::InvalidBoard);
     }
+    let (mines, moves) = rest.split_at(bitset_bytes(len));
+    let moves = moves_from_bytes(moves, w, h).ok_or(LoadError::InvalidBoard)?;
 
-    #[allow(dead_code)]
-    pub fn dump(&self, x: u8, y: u8) -> Option<String> {
-        self.cell(x, y).map(|cell| {
-            format!(
-                "{:?}, adjacent mines: {}",
-                cell,
-                self.adj(x, y)
-                    .into_iter()
-                    .filter(|(x, y)| self.cell(*x, *y).unwrap().mine)
-                    .count()
-            )
+    let cells = (0..len)
+        .map(|idx| GameCell {
+            state: CellState::Covered,
+            mine: mines[idx / 8] & (1 << (idx % 8)) != 0,
         })
-    }
+        .collect();
+    let game = Game::from_cells(w, h, cells).ok_or(LoadError::InvalidBoard)?;
+    Ok((game, moves))
+}
 
-    pub fn open(&mut self, x: u8, y: u8) {
-        let cell = if let Some(cell) = self.cell(x, y) {
-            cell
-        } else {
-            return;
+impl Game {
+    pub fn new(w: u8, h: u8) -> Self {
+        let mut game = Self {
+            h,
+            w,
+            cells: (0..h as usize * w as usize)
+                .map(|_| GameCell::new())
+                .collect(),
+            state: GameState::Continue,
+            question_marks: false,
+            win_condition: WinCondition::OpenAllSafe,
+            mulligan: false,
+            mulligan_used: false,
+            opened: false,
+            started: false,
+            history: Vec::new(),
         };
+        game.ensure_safe_cell();
+        game
+    }
 
-        trace!("User clicked on {:#?}", cell);
-        if cell.mine {
-            self.state = GameState::Lost;
-            return;
-        }
-
-        let mut visited = vec![false; self.cells.len()];
-        self.visit(&mut visited, x, y);
+    /// Creates a game with exactly `mines` randomly placed mines
+    ///
+    /// At least one cell is always left without a mine, so asking for too many
+    /// mines gets one less mine than there are cells.
+    pub fn with_mines(w: u8, h: u8, mines: usize) -> Self {
+        Self::generate(w, h, mines, &RandomGenerator)
+    }
 
-        if self
-            .cells
-            .iter()
-            .find(|c| c.state == CellState::Covered && !c.mine)
-            .is_none()
-        {
-            self.state = GameState::Won;
-        }
+    /// Creates a game with exactly `mines` mines, laid out the same way for the same `seed`
+    ///
+    /// Like [Game::with_mines] asking for `w * h` mines or more gets `w * h - 1`.
+    pub fn with_config(w: u8, h: u8, mines: usize, seed: u64) -> Self {
+        Self::generate(w, h, mines, &SeededGenerator { seed })
     }
 
-    pub fn flag(&mut self, x: u8, y: u8) -> Option<bool> {
-        let cell = if let Some(cell) = self.cell_mut(x, y) {
-            cell
-        } else {
-            return None;
+    /// Creates a game with mines placed by the given [BoardGenerator]
+    ///
+    /// If the generator mines every cell, one of the mines is removed.
+    pub fn generate(w: u8, h: u8, mines: usize, generator: &dyn BoardGenerator) -> Self {
+        let mut game = Self {
+            h,
+            w,
+            cells: generator.generate(w, h, mines),
+            state: GameState::Continue,
+            question_marks: false,
+            win_condition: WinCondition::OpenAllSafe,
+            mulligan: false,
+            mulligan_used: false,
+            opened: false,
+            started: false,
+            history: Vec::new(),
         };
+        game.ensure_safe_cell();
+        game
+    }
 
-        match cell.state {
-            CellState::Covered => {
-                cell.state = CellState::Flagged;
-                Some(true)
-            }
-            CellState::Flagged => {
-                cell.state = CellState::Covered;
-                Some(false)
-            }
-            CellState::Uncovered => None,
+    /// Creates a game from the given cells, laid out row by row
+    ///
+    /// Returns `None` if the number of cells doesn't match the dimensions
+    /// or there are no cells without a mine.
+    pub fn from_cells(w: u8, h: u8, cells: Vec<GameCell>) -> Option<Self> {
+        if cells.len() != h as usize * w as usize {
+            return None;
         }
-    }
 
-    fn cell(&self, x: u8, y: u8) -> Option<&GameCell> {
-        if !(x >= self.w || y >= self.h) {
-            self.cells.get((y * self.w + x) as usize)
+        let opened = cells.iter().any(|c| c.state == CellState::Uncovered);
+        let started = cells.iter().any(|c| c.state != CellState::Covered);
+        let game = Self {
+            h,
+            w,
+            cells,
+            state: GameState::Continue,
+            question_marks: false,
+            win_condition: WinCondition::OpenAllSafe,
+            mulligan: false,
+            mulligan_used: false,
+            opened,
+            started,
+            history: Vec::new(),
+        };
+        if game.has_safe_cell() {
+            Some(game)
         } else {
             None
         }
     }
 
-    fn cell_mut(&mut self, x: u8, y: u8) -> Option<&mut GameCell> {
-        if !(x >= self.w || y >= self.h) {
-            self.cells.get_mut((y * self.w + x) as usize)
-        } else {
-            None
-        }
+    /// Checks whether there is at least one cell without a mine
+    pub fn has_safe_cell(&self) -> bool {
+        self.cells.iter().any(|c| !c.mine)
     }
 
-    fn adj(&self, x: u8, y: u8) -> Vec<(u8, u8)> {
-        let mut adjacent = vec![];
+    /// Removes a random mine if every cell has one
+    fn ensure_safe_cell(&mut self) {
+        if self.has_safe_cell() {
+            return;
+        }
+        if let Some(cell) = self.cells.choose_mut(&mut thread_rng()) {
+            cell.mine = false;
+        }
+    }
 
-        if let Some(y) = y.checked_sub(1) {
-            if let Some(x) = x.checked_sub(1) {
-                adjacent.push((x, y));
-            }
+    /// Serializes the game into a [SavedGame]
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&SavedGame {
+            version: SAVE_VERSION,
+            game: self.clone(),
+        })
+        .expect("Failed to serialize the game")
+    }
 
-            adjacent.push((x, y));
+    /// Loads a game saved with [Game::to_json]
+    ///
+    /// The version is checked before anything else, so saves from other
+    /// versions are rejected even if their layout changed completely.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, LoadError> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
 
-            if x + 1 < self.w {
-                adjacent.push((x + 1, y));
-            }
+        let Version { version } = serde_json::from_str(json)?;
+        if version != SAVE_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
         }
 
-        if let Some(x) = x.checked_sub(1) {
-            adjacent.push((x, y));
+        let SavedGame { mut game, .. } = serde_json::from_str(json)?;
+        if game.cells.len() != game.h as usize * game.w as usize {
+            return Err(LoadError::InvalidBoard);
         }
+        // Saves from before first-click safety don't have the field
+        game.opened |= game.cells.iter().any(|c| c.state == CellState::Uncovered);
+        Ok(game)
+    }
 
-        if x + 1 < self.w {
-            adjacent.push((x + 1, y));
+    /// Serializes the game into a compact binary form
+    ///
+    /// Much smaller than [Game::to_json] on large boards: a header with the
+    /// version, the dimensions as little endian `u16`s, the [GameState] and
+    /// the options and flags, followed by 2 bits per cell for its [CellState],
+    /// a bit per cell for its mine and three bytes per move of the
+    /// [Game::history].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.cells.len();
+        let mut bytes = vec![BYTES_VERSION];
+        bytes.extend_from_slice(&(self.w as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.h as u16).to_le_bytes());
+        bytes.push(match self.state {
+            GameState::Continue => 0,
+            GameState::Won => 1,
+            GameState::Lost => 2,
+        });
+        let mut options = 0;
+        if self.question_marks {
+            options |= 1;
+        }
+        if self.win_condition == WinCondition::FlagAllMines {
+            options |= 2;
+        }
+        if self.mulligan {
+            options |= 4;
+        }
+        if self.mulligan_used {
+            options |= 8;
+        }
+        if self.opened {
+            options |= 16;
+        }
+        if self.started {
+            options |= 32;
+        }
+        bytes.push(options);
+
+        let header = bytes.len();
+        let mines = header + bitset_bytes(2 * len);
+        bytes.resize(mines + bitset_bytes(len), 0);
+        for (idx, cell) in self.cells.iter().enumerate() {
+            let state: u8 = match cell.state {
+                CellState::Uncovered => 0,
+                CellState::Covered => 1,
+                CellState::Flagged => 2,
+                CellState::Questioned => 3,
+            };
+            bytes[header + idx / 4] |= state << (2 * (idx % 4));
+            if cell.mine {
+                bytes[mines + idx / 8] |= 1 << (idx % 8);
+            }
         }
+        bytes.extend(moves_to_bytes(&self.history));
 
-        if y + 1 < self.h {
-            let y = y + 1;
+        bytes
+    }
 
-            if let Some(x) = x.checked_sub(1) {
-                adjacent.push((x, y));
-            }
+    /// Loads a game saved with [Game::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        let (version, w, h, state, options, cells) = match bytes {
+            [version, w0, w1, h0, h1, state, options, cells @ ..] => (
+                *version,
+                u16::from_le_bytes([*w0, *w1]),
+                u16::from_le_bytes([*h0, *h1]),
+                *state,
+                *options,
+                cells,
+            ),
+            _ => return Err(LoadError::InvalidBoard),
+        };
+        if version != BYTES_VERSION {
+            return Err(LoadError::UnsupportedVersion(version as u32));
+        }
 
-            adjacent.push((x, y));
+        let (w, h) = match (u8::try_from(w), u8::try_from(h)) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => return Err(LoadError::InvalidBoard),
+        };
+        let state = match state {
+            0 => GameState::Continue,
+            1 => GameState::Won,
+            2 => GameState::Lost,
+            _ => return Err(LoadError::InvalidBoard),
+        };
 
-            if x + 1 < self.w {
-                adjacent.push((x + 1, y));
-            }
+        let len = w as usize * h as usize;
+        let mines = bitset_bytes(2 * len);
+        let moves = mines + bitset_bytes(len);
+        if cells.len() < moves {
+            return Err(LoadError::InvalidBoard);
         }
+        let history = moves_from_bytes(&cells[moves..], w, h).ok_or(LoadError::InvalidBoard)?;
+        let cells = (0..len)
+            .map(|idx| GameCell {
+                state: match (cells[idx / 4] >> (2 * (idx % 4))) & 0b11 {
+                    0 => CellState::Uncovered,
+                    1 => CellState::Covered,
+                    2 => CellState::Flagged,
+                    _ => CellState::Questioned,
+                },
+                mine: cells[mines + idx / 8] & (1 << (idx % 8)) != 0,
+            })
+            .collect();
+
+        let mut game = Game::from_cells(w, h, cells).ok_or(LoadError::InvalidBoard)?;
+        game.state = state;
+        game.question_marks = options & 1 != 0;
+        if options & 2 != 0 {
+            game.win_condition = WinCondition::FlagAllMines;
+        }
+        game.mulligan = options & 4 != 0;
+        game.mulligan_used = options & 8 != 0;
+        game.opened = options & 16 != 0;
+        game.started = options & 32 != 0;
+        game.history = history;
+        Ok(game)
+    }
 
-        adjacent
+    /// Generates a new board for the given preset
+    pub fn from_difficulty(level: DifficultyLevel) -> Self {
+        Self::from_params(level.params())
     }
 
-    fn visit(&mut self, visited: &mut [bool], x: u8, y: u8) {
-        let cell_idx = (y * self.w + x) as usize;
+    /// Generates a new board with the same parameters
+    pub fn from_params(params: GameParams) -> Self {
+        Self::with_mines(params.width, params.height, params.mines)
+    }
 
-        if visited[cell_idx] {
-            return;
+    /// Moves made with [Game::apply_move] so far, in order
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Checks whether any cell has been opened or flagged yet
+    ///
+    /// Stays `true` once a move was made, even if it was undone by unflagging.
+    pub fn has_started(&self) -> bool {
+        self.started || self.is_over() || self.cells.iter().any(|c| c.state != CellState::Covered)
+    }
+
+    pub fn params(&self) -> GameParams {
+        GameParams {
+            width: self.w,
+            height: self.h,
+            mines: self.mines(),
         }
+    }
 
-        let adj = self.adj(x, y);
-        let mines = adj
-            .iter()
-            .filter(|(x, y)| self.cells[(y * self.w + x) as usize].mine)
-            .count();
-        let cell = &mut self.cells[cell_idx];
+    /// Enables or disables question marks (see [Game::flag])
+    pub fn set_question_marks(&mut self, enabled: bool) {
+        self.question_marks = enabled;
+    }
+
+    /// Changes what it takes to win the game (see [WinCondition])
+    pub fn set_win_condition(&mut self, condition: WinCondition) {
+        self.win_condition = condition;
+    }
+
+    pub fn win_condition(&self) -> WinCondition {
+        self.win_condition
+    }
 
-        trace!("Visiting {:?}, adjacent mines: {}", cell, mines);
+    /// Lets the player survive opening a single mine per game
+    pub fn set_mulligan(&mut self, enabled: bool) {
+        self.mulligan = enabled;
+    }
 
-        cell.state = CellState::Uncovered;
-        visited[cell_idx] = true;
+    /// Checks whether the mulligan was spent (see [Game::set_mulligan])
+    pub fn mulligan_used(&self) -> bool {
+        self.mulligan_used
+    }
 
-        if adj
+    /// Moves the mine at the given coordinates to another covered cell
+    ///
+    /// Cells away from the uncovered ones are preferred, so the numbers
+    /// the player already saw don't change. With `clear_around` the mine
+    /// isn't moved next to the cell either, unless there's no room elsewhere.
+    /// The target is picked with [fnv1a] over the mines and the coordinates,
+    /// so it only depends on the board and replaying the same moves relocates
+    /// the mine the same way on every build.
+    /// Returns `false` if there's nowhere to move the mine.
+    fn relocate_mine(&mut self, x: u8, y: u8, clear_around: bool) -> bool {
+        let neighbors = self.adj(x, y);
+        let mut candidates: Vec<(u8, u8)> = (0..self.h)
+            .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+            .filter(|&(cx, cy)| {
+                let cell = &self.cells[self.index(cx, cy)];
+                (cx, cy) != (x, y) && !cell.mine && cell.state == CellState::Covered
+            })
+            .collect();
+        if clear_around && candidates.iter().any(|c| !neighbors.contains(c)) {
+            candidates.retain(|c| !neighbors.contains(c));
+        }
+        let hidden: Vec<(u8, u8)> = candidates
             .iter()
-            .find(|(x, y)| self.cells[(y * self.w + x) as usize].mine)
-            .is_none()
-        {
-            // Adjacent cells don't have mines. Keep opening...
-            let to_visit: Vec<_> = adj
-                .into_iter()
-                .filter(|(x, y)| {
-                    self.cells[(y * self.w + x) as usize].state != CellState::Uncovered
-                })
-                .collect();
-            for (x, y) in to_visit {
-                self.visit(visited, x, y);
+            .copied()
+            .filter(|&(cx, cy)| !self.is_frontier(cx, cy))
+            .collect();
+
+        let pool = if hidden.is_empty() {
+            &candidates
+        } else {
+            &hidden
+        };
+        if pool.is_empty() {
+            return false;
+        }
+
+        let mut key = vec![x, y];
+        key.resize(2 + bitset_bytes(self.cells.len()), 0);
+        for (idx, cell) in self.cells.iter().enumerate() {
+            if cell.mine {
+                key[2 + idx / 8] |= 1 << (idx % 8);
             }
         }
+        let (tx, ty) = pool[(fnv1a(&key) % pool.len() as u64) as usize];
+
+        let from = self.index(x, y);
+        let to = self.index(tx, ty);
+        self.cells[from].mine = false;
+        self.cells[to].mine = true;
+        true
     }
-}
 
-impl std::fmt::Display for Game {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}\n",
-            (0..self.w)
-                .into_iter()
-                .fold(String::from("   "), |mut s, c| {
-                    s += &format!("{}   ", c);
-                    s
-                })
+    /// Checks whether the [WinCondition] is met
+    fn is_solved(&self) -> bool {
+        match self.win_condition {
+            // Flagging a safe cell doesn't count as opening it
+            WinCondition::OpenAllSafe => self
+                .cells
+                .iter()
+                .all(|c| c.mine || c.state == CellState::Uncovered),
+            WinCondition::FlagAllMines => self
+                .cells
+                .iter()
+                .all(|c| c.mine == (c.state == CellState::Flagged)),
+        }
+    }
+
+    /// Marks the game as won if the [WinCondition] is met
+    fn check_win(&mut self) {
+        if self.state == GameState::Continue && self.is_solved() {
+            self.state = GameState::Won;
+        }
+    }
+
+    pub fn height(&self) -> u8 {
+        self.h
+    }
+
+    pub fn width(&self) -> u8 {
+        self.w
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.state == GameState::Won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.state == GameState::Lost
+    }
+
+    /// Checks whether the game is either won or lost
+    pub fn is_over(&self) -> bool {
+        self.state != GameState::Continue
+    }
+
+    pub fn cell_state(&self, x: u8, y: u8) -> Option<CellState> {
+        self.cell(x, y).map(|cell| cell.state)
+    }
+
+    pub fn has_mine(&self, x: u8, y: u8) -> Option<bool> {
+        self.cell(x, y).map(|cell| cell.mine)
+    }
+
+    pub fn adjacent_mines(&self, x: u8, y: u8) -> Option<usize> {
+        self.cell(x, y).and_then(|_| {
+            let mines = self
+                .adj(x, y)
+                .into_iter()
+                .filter(|(x, y)| self.cells[self.index(*x, *y)].mine)
+                .count();
+
+            Some(mines)
+        })
+    }
+
+    /// Number of adjacent mines as seen by the player
+    ///
+    /// Unlike [Game::adjacent_mines] it's `None` unless the cell is uncovered,
+    /// so it never leaks hidden information.
+    pub fn visible_number(&self, x: u8, y: u8) -> Option<usize> {
+        match self.cell_state(x, y) {
+            Some(CellState::Uncovered) => self.adjacent_mines(x, y),
+            _ => None,
+        }
+    }
+
+    /// Flags the remaining cells once every safe cell is uncovered and wins the game
+    ///
+    /// That's the case when there are as many not uncovered cells as there
+    /// are mines, no matter where the flags are. With [WinCondition::OpenAllSafe]
+    /// the game is already won by then and only the mines get flagged, with
+    /// [WinCondition::FlagAllMines] it saves flagging them one by one: the
+    /// flags are [Move::Flag]s, kept in the [Game::history] like any other.
+    /// Returns `true` if any cell got flagged.
+    pub fn try_auto_complete(&mut self) -> bool {
+        if self.state == GameState::Lost {
+            return false;
+        }
+
+        let unknown = self
+            .cells
+            .iter()
+            .filter(|c| c.state != CellState::Uncovered)
+            .count();
+        if unknown != self.mines() {
+            return false;
+        }
+
+        // Every cell left is a mine
+        if self.win_condition == WinCondition::OpenAllSafe {
+            self.state = GameState::Won;
+            return self.flag_all_mines();
+        }
+
+        let mut flagged = false;
+        for y in 0..self.h {
+            for x in 0..self.w {
+                // Question marks take two moves to become flags
+                while self.cell_state(x, y) != Some(CellState::Flagged)
+                    && self.apply_move(Move::Flag(x, y)).changed()
+                {
+                    flagged = true;
+                }
+            }
+        }
+        flagged
+    }
+
+    /// Flags every mine which isn't flagged yet, e.g. to show a won board
+    ///
+    /// Doesn't touch the game state. Returns `true` if any cell got flagged.
+    pub fn flag_all_mines(&mut self) -> bool {
+        let mut flagged = false;
+        for cell in self.cells.iter_mut() {
+            if cell.mine && cell.state != CellState::Flagged {
+                cell.state = CellState::Flagged;
+                flagged = true;
+            }
+        }
+        flagged
+    }
+
+    /// Returns how many more flags an uncovered cell needs around it
+    ///
+    /// Negative if there are more flags than adjacent mines. Returns `None`
+    /// for cells which aren't uncovered.
+    pub fn remaining_mines_around(&self, x: u8, y: u8) -> Option<i32> {
+        let mines = self.visible_number(x, y)?;
+        let flags = self.adjacent_flags(x, y)?;
+        Some(mines as i32 - flags as i32)
+    }
+
+    /// Groups the cells without adjacent mines into connected regions
+    ///
+    /// Each region holds the cells a single click on any of its empty cells
+    /// would open: the empty cells and the numbers around them. A number
+    /// bordering several regions belongs to each of them. Cell states are
+    /// ignored, regions only depend on the mines.
+    pub fn open_regions(&self) -> Vec<Vec<(u8, u8)>> {
+        let mut visited = vec![false; self.cells.len()];
+        let mut regions = vec![];
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let idx = self.index(x, y);
+                if visited[idx] || self.cells[idx].mine || self.adjacent_mines(x, y) != Some(0) {
+                    continue;
+                }
+
+                let mut region = self.flood(x, y, |_| true);
+                for &(x, y) in region.iter() {
+                    visited[self.index(x, y)] = true;
+                }
+                region.sort_by_key(|&(x, y)| (y, x));
+                regions.push(region);
+            }
+        }
+
+        regions
+    }
+
+    /// Returns the cells [Game::open] would uncover, without uncovering them
+    ///
+    /// Opening an empty cell cascades to its neighbors, flagged cells stop
+    /// the cascade. Empty if the cell can't be opened or has a mine.
+    pub fn flood_from(&self, x: u8, y: u8) -> Vec<(u8, u8)> {
+        match self.cell(x, y) {
+            Some(cell)
+                if !cell.mine
+                    && matches!(cell.state, CellState::Covered | CellState::Questioned) =>
+            {
+                self.flood(x, y, |cell| {
+                    matches!(cell.state, CellState::Covered | CellState::Questioned)
+                })
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Finds the covered cells a single number decides, taking flags as mines
+    ///
+    /// A number needing as many more flags as it has covered neighbors makes
+    /// them all mines, a number with all its flags makes them all safe. Only
+    /// uses what the player can see. Wrong flags can put a cell in both lists.
+    pub fn analyze(&self) -> Analysis {
+        let mut mines = vec![false; self.cells.len()];
+        let mut safes = vec![false; self.cells.len()];
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let remaining = match self.remaining_mines_around(x, y) {
+                    Some(remaining) => remaining,
+                    None => continue,
+                };
+                let covered: Vec<_> = self.covered_neighbors(x, y).collect();
+                let forced = if remaining == 0 {
+                    &mut safes
+                } else if remaining == covered.len() as i32 {
+                    &mut mines
+                } else {
+                    continue;
+                };
+                for (x, y) in covered {
+                    forced[self.index(x, y)] = true;
+                }
+            }
+        }
+
+        let cells = |forced: Vec<bool>| {
+            (0..self.h)
+                .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+                .filter(|(x, y)| forced[self.index(*x, *y)])
+                .collect()
+        };
+        Analysis {
+            forced_mines: cells(mines),
+            forced_safes: cells(safes),
+        }
+    }
+
+    /// Checks whether a still unknown cell borders an uncovered one
+    ///
+    /// These are the cells deductions can be made about. Uncovered,
+    /// flagged and out of bounds cells are never on the frontier.
+    pub fn is_frontier(&self, x: u8, y: u8) -> bool {
+        match self.cell_state(x, y) {
+            Some(CellState::Covered) | Some(CellState::Questioned) => self
+                .adj(x, y)
+                .into_iter()
+                .any(|(x, y)| self.cells[self.index(x, y)].state == CellState::Uncovered),
+            _ => false,
+        }
+    }
+
+    pub fn adjacent_flags(&self, x: u8, y: u8) -> Option<usize> {
+        self.cell(x, y)
+            .map(|_| self.flagged_neighbors(x, y).count())
+    }
+
+    /// Neighbors of the cell which are still covered, question marks included
+    ///
+    /// Empty if the cell is out of bounds.
+    pub fn covered_neighbors(&self, x: u8, y: u8) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.neighbors_where(x, y, |state| {
+            matches!(state, CellState::Covered | CellState::Questioned)
+        })
+    }
+
+    /// Flagged neighbors of the cell, empty if the cell is out of bounds
+    pub fn flagged_neighbors(&self, x: u8, y: u8) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.neighbors_where(x, y, |state| state == CellState::Flagged)
+    }
+
+    fn neighbors_where(
+        &self,
+        x: u8,
+        y: u8,
+        filter: impl Fn(CellState) -> bool + 'static,
+    ) -> impl Iterator<Item = (u8, u8)> + '_ {
+        let neighbors = match self.cell(x, y) {
+            Some(_) => self.adj(x, y),
+            None => vec![],
+        };
+        neighbors
+            .into_iter()
+            .filter(move |(x, y)| filter(self.cells[self.index(*x, *y)].state))
+    }
+
+    pub fn mines(&self) -> usize {
+        self.cells.iter().filter(|c| c.mine).count()
+    }
+
+    /// Counts the mines which aren't flagged, ignoring wrongly placed flags
+    pub fn unflagged_mines(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| c.mine && c.state != CellState::Flagged)
+            .count()
+    }
+
+    pub fn flagged(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| c.state == CellState::Flagged)
+            .count()
+    }
+
+    /// Counts the flags which are placed on mines
+    pub fn correctly_flagged(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| c.mine && c.state == CellState::Flagged)
+            .count()
+    }
+
+    /// Counts the cells which are neither uncovered nor flagged
+    pub fn covered_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| matches!(c.state, CellState::Covered | CellState::Questioned))
+            .count()
+    }
+
+    pub fn inspect(&self, x: u8, y: u8) -> Option<CellInfo> {
+        self.cell(x, y).map(|cell| {
+            let neighbors = self.adj(x, y);
+            let adjacent = neighbors
+                .iter()
+                .filter(|(x, y)| self.cells[self.index(*x, *y)].mine)
+                .count();
+
+            CellInfo {
+                state: cell.state,
+                mine: cell.mine,
+                adjacent,
+                neighbors,
+            }
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn dump(&self, x: u8, y: u8) -> Option<String> {
+        self.inspect(x, y).map(|info| info.to_string())
+    }
+
+    /// Makes a move, the common entry point for everything the player does
+    ///
+    /// Unlike calling [Game::open], [Game::flag] or [Game::chord] directly,
+    /// moves are ignored once the game is over, and the ones that change the
+    /// board are kept in the [Game::history].
+    pub fn apply_move(&mut self, m: Move) -> MoveResult {
+        if self.is_over() {
+            return MoveResult::Ignored;
+        }
+
+        let changed = match m {
+            Move::Open(x, y) => self.open(x, y),
+            Move::Flag(x, y) => self.flag(x, y).is_some(),
+            Move::Chord(x, y) => self.chord(x, y),
+        };
+
+        if changed {
+            self.history.push(m);
+        }
+        match (changed, self.state) {
+            (false, _) => MoveResult::Ignored,
+            (true, GameState::Continue) => MoveResult::Changed,
+            (true, state) => MoveResult::Over(state),
+        }
+    }
+
+    /// Opens the cell, uncovering its mine-free surroundings
+    ///
+    /// Returns `false` if nothing changed, i.e. the cell is out of bounds,
+    /// already uncovered or flagged. Question marks don't prevent opening.
+    /// The first cell opened never has a mine: if it does, the mine is moved
+    /// away from it and its neighbors. With the mulligan enabled (see
+    /// [Game::set_mulligan]) the first mine opened later on is moved away
+    /// instead of losing the game.
+    pub fn open(&mut self, x: u8, y: u8) -> bool {
+        let cell = match self.cell(x, y) {
+            Some(cell) if matches!(cell.state, CellState::Covered | CellState::Questioned) => cell,
+            _ => return false,
+        };
+
+        trace!("User clicked on {:#?}", cell);
+        if cell.mine {
+            if !self.opened && self.relocate_mine(x, y, true) {
+                return self.open(x, y);
+            }
+            if self.mulligan && !self.mulligan_used && self.relocate_mine(x, y, false) {
+                self.mulligan_used = true;
+                return self.open(x, y);
+            }
+            self.state = GameState::Lost;
+            return true;
+        }
+
+        for (x, y) in self.flood_from(x, y) {
+            let idx = self.index(x, y);
+            self.cells[idx].state = CellState::Uncovered;
+        }
+        self.opened = true;
+        self.started = true;
+        self.check_win();
+
+        true
+    }
+
+    /// Cycles the cell through its covered states: covered -> flagged -> covered
+    ///
+    /// With question marks enabled the cycle is covered -> flagged -> questioned
+    /// -> covered. Returns whether the cell is flagged now or `None` if it can't be
+    /// flagged.
+    /// Flagging the last mine wins a [WinCondition::FlagAllMines] game.
+    pub fn flag(&mut self, x: u8, y: u8) -> Option<bool> {
+        let question_marks = self.question_marks;
+        let cell = if let Some(cell) = self.cell_mut(x, y) {
+            cell
+        } else {
+            return None;
+        };
+
+        let flagged = match cell.state {
+            CellState::Covered => {
+                cell.state = CellState::Flagged;
+                true
+            }
+            CellState::Flagged if question_marks => {
+                cell.state = CellState::Questioned;
+                false
+            }
+            CellState::Flagged | CellState::Questioned => {
+                cell.state = CellState::Covered;
+                false
+            }
+            CellState::Uncovered => return None,
+        };
+        self.started = true;
+        self.check_win();
+
+        Some(flagged)
+    }
+
+    /// Flags (or unflags) every cell in the rectangle between `(x0, y0)` and `(x1, y1)`
+    ///
+    /// The rectangle is inclusive and clamped to the board. Uncovered cells
+    /// are skipped, questioned cells get flagged but not unflagged.
+    /// Returns the cells which changed.
+    pub fn flag_region(&mut self, x0: u8, y0: u8, x1: u8, y1: u8, flag: bool) -> Vec<(u8, u8)> {
+        if self.w == 0 || self.h == 0 {
+            return vec![];
+        }
+
+        let (x0, x1) = (x0.min(x1), x0.max(x1).min(self.w - 1));
+        let (y0, y1) = (y0.min(y1), y0.max(y1).min(self.h - 1));
+
+        let mut changed = vec![];
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let idx = self.index(x, y);
+                let cell = &mut self.cells[idx];
+                let state = match (cell.state, flag) {
+                    (CellState::Covered, true) | (CellState::Questioned, true) => {
+                        CellState::Flagged
+                    }
+                    (CellState::Flagged, false) => CellState::Covered,
+                    _ => continue,
+                };
+                cell.state = state;
+                changed.push((x, y));
+            }
+        }
+        self.started |= !changed.is_empty();
+        self.check_win();
+
+        changed
+    }
+
+    /// Opens every covered cell in the rectangle between `(x0, y0)` and `(x1, y1)`
+    ///
+    /// The rectangle is inclusive and clamped to the board. Flagged cells are
+    /// skipped. Stops as soon as a mine is opened, losing the game.
+    /// Returns all the cells uncovered along the way.
+    pub fn open_region(&mut self, x0: u8, y0: u8, x1: u8, y1: u8) -> Vec<(u8, u8)> {
+        if self.w == 0 || self.h == 0 {
+            return vec![];
+        }
+
+        let covered: Vec<bool> = self
+            .cells
+            .iter()
+            .map(|c| c.state != CellState::Uncovered)
+            .collect();
+        let (x0, x1) = (x0.min(x1), x0.max(x1).min(self.w - 1));
+        let (y0, y1) = (y0.min(y1), y0.max(y1).min(self.h - 1));
+
+        'region: for y in y0..=y1 {
+            for x in x0..=x1 {
+                self.open(x, y);
+                if self.state == GameState::Lost {
+                    break 'region;
+                }
+            }
+        }
+
+        (0..self.h)
+            .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                let idx = self.index(*x, *y);
+                covered[idx] && self.cells[idx].state == CellState::Uncovered
+            })
+            .collect()
+    }
+
+    /// Opens a random covered cell which doesn't have a mine
+    ///
+    /// Returns the coordinates of the opened cell or `None` if the game is over.
+    pub fn hint(&mut self) -> Option<(u8, u8)> {
+        if self.state != GameState::Continue {
+            return None;
+        }
+
+        let safe: Vec<_> = (0..self.h)
+            .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                let cell = &self.cells[self.index(*x, *y)];
+                !cell.mine && matches!(cell.state, CellState::Covered | CellState::Questioned)
+            })
+            .collect();
+        let (x, y) = *safe.choose(&mut thread_rng())?;
+
+        self.apply_move(Move::Open(x, y));
+        Some((x, y))
+    }
+
+    /// Checks whether the cell is an uncovered number whose adjacent mines
+    /// are all accounted for by flags.
+    pub fn is_satisfied(&self, x: u8, y: u8) -> bool {
+        match (
+            self.cell_state(x, y),
+            self.adjacent_mines(x, y),
+            self.adjacent_flags(x, y),
+        ) {
+            (Some(CellState::Uncovered), Some(mines), Some(flags)) => mines > 0 && mines == flags,
+            _ => false,
+        }
+    }
+
+    /// Returns the cells [Game::chord] would open, without opening them
+    ///
+    /// Empty unless the cell is a satisfied number.
+    pub fn chord_preview(&self, x: u8, y: u8) -> Vec<(u8, u8)> {
+        if !self.is_satisfied(x, y) {
+            return vec![];
+        }
+
+        self.covered_neighbors(x, y).collect()
+    }
+
+    /// Opens all covered neighbors of a satisfied number (see [Game::is_satisfied]).
+    ///
+    /// Does nothing otherwise. Opening a mine by chording loses the game just like
+    /// opening it directly. Returns `false` if nothing was opened.
+    pub fn chord(&mut self, x: u8, y: u8) -> bool {
+        let mut opened = false;
+        for (x, y) in self.chord_preview(x, y) {
+            opened |= self.open(x, y);
+            if self.state == GameState::Lost {
+                break;
+            }
+        }
+
+        opened
+    }
+
+    fn cell(&self, x: u8, y: u8) -> Option<&GameCell> {
+        if !(x >= self.w || y >= self.h) {
+            self.cells.get(self.index(x, y))
+        } else {
+            None
+        }
+    }
+
+    fn cell_mut(&mut self, x: u8, y: u8) -> Option<&mut GameCell> {
+        if !(x >= self.w || y >= self.h) {
+            let idx = self.index(x, y);
+            self.cells.get_mut(idx)
+        } else {
+            None
+        }
+    }
+
+    /// Index of the cell in [Game::cells]
+    ///
+    /// Computed in `usize`, since boards with 256 or more cells overflow `u8`.
+    fn index(&self, x: u8, y: u8) -> usize {
+        y as usize * self.w as usize + x as usize
+    }
+
+    fn adj(&self, x: u8, y: u8) -> Vec<(u8, u8)> {
+        let mut adjacent = vec![];
+
+        if let Some(y) = y.checked_sub(1) {
+            if let Some(x) = x.checked_sub(1) {
+                adjacent.push((x, y));
+            }
+
+            adjacent.push((x, y));
+
+            if x + 1 < self.w {
+                adjacent.push((x + 1, y));
+            }
+        }
+
+        if let Some(x) = x.checked_sub(1) {
+            adjacent.push((x, y));
+        }
+
+        if x + 1 < self.w {
+            adjacent.push((x + 1, y));
+        }
+
+        if y + 1 < self.h {
+            let y = y + 1;
+
+            if let Some(x) = x.checked_sub(1) {
+                adjacent.push((x, y));
+            }
+
+            adjacent.push((x, y));
+
+            if x + 1 < self.w {
+                adjacent.push((x + 1, y));
+            }
+        }
+
+        adjacent
+    }
+
+    /// Collects the cells reachable from `(x, y)` through cells without
+    /// adjacent mines, passing only the cells `passable` accepts
+    fn flood(&self, x: u8, y: u8, passable: impl Fn(&GameCell) -> bool) -> Vec<(u8, u8)> {
+        let mut visited = vec![false; self.cells.len()];
+        let mut region = vec![(x, y)];
+        let mut to_visit = vec![(x, y)];
+        visited[self.index(x, y)] = true;
+        while let Some((x, y)) = to_visit.pop() {
+            trace!("Visiting ({}, {})", x, y);
+            if self.adjacent_mines(x, y) != Some(0) {
+                continue;
+            }
+
+            // Adjacent cells don't have mines. Keep opening...
+            for (x, y) in self.adj(x, y) {
+                let idx = self.index(x, y);
+                if !visited[idx] && passable(&self.cells[idx]) {
+                    visited[idx] = true;
+                    region.push((x, y));
+                    to_visit.push((x, y));
+                }
+            }
+        }
+
+        region
+    }
+}
+
+impl std::fmt::Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n",
+            (0..self.w)
+                .into_iter()
+                .fold(String::from("   "), |mut s, c| {
+                    s += &format!("{}   ", c);
+                    s
+                })
         )?;
         for y in 0..self.h {
             write!(f, "{} ", y)?;
@@ -291,104 +1502,758 @@ impl std::fmt::Display for Game {
                     self.adjacent_mines(x, y).unwrap()
                 )?;
             }
-            write!(f, "\n")?;
+            write!(f, "\n")?;
+        }
+        write!(f, "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn getters() {
+        const H: u8 = 6;
+        const W: u8 = 5;
+        let mut game = Game::new(W, H);
+
+        assert_eq!(game.height(), H);
+        assert_eq!(game.width(), W);
+        assert_eq!(game.state(), GameState::Continue);
+
+        // Cell state
+        // After initialization all cells should be covered
+        for y in 0..H {
+            for x in 0..W {
+                assert_eq!(game.cell_state(x, y), Some(CellState::Covered));
+            }
+        }
+        // Check out of bounds access
+        assert_eq!(game.cell_state(W, H), None);
+        assert_eq!(game.cell_state(H - 1, W - 1), None);
+
+        // Flagged
+        game.cells = vec![GameCell::default(); (H * W) as usize];
+        game.cell_mut(0, 0).unwrap().state = CellState::Flagged;
+        game.cell_mut(W - 1, H - 1).unwrap().state = CellState::Flagged;
+        assert_eq!(game.flagged(), 2);
+
+        // Mines
+        // Place a mine into the first cell and check that its reflected correctly
+        game.cell_mut(0, 0).unwrap().mine = true;
+        assert_eq!(game.has_mine(0, 0), Some(true));
+        // Check out of bounds access
+        assert_eq!(game.has_mine(W, H), None);
+        assert_eq!(game.has_mine(H - 1, W - 1), None);
+        // Check adjacent mines
+        game.cells = vec![GameCell::default(); (H * W) as usize];
+        // No mines at all
+        for y in 0..H {
+            for x in 0..W {
+                assert_eq!(game.adjacent_mines(x, y), Some(0));
+            }
+        }
+        // 12321
+        // 2xxx2
+        // 3x8x3
+        // 2xxx2
+        // 12321
+        // 00000
+        game.cell_mut(1, 1).unwrap().mine = true;
+        game.cell_mut(2, 1).unwrap().mine = true;
+        game.cell_mut(3, 1).unwrap().mine = true;
+        game.cell_mut(1, 2).unwrap().mine = true;
+        game.cell_mut(3, 2).unwrap().mine = true;
+        game.cell_mut(1, 3).unwrap().mine = true;
+        game.cell_mut(2, 3).unwrap().mine = true;
+        game.cell_mut(3, 3).unwrap().mine = true;
+
+        assert_eq!(game.adjacent_mines(0, 0), Some(1));
+        assert_eq!(game.adjacent_mines(1, 0), Some(2));
+        assert_eq!(game.adjacent_mines(2, 0), Some(3));
+        assert_eq!(game.adjacent_mines(3, 0), Some(2));
+        assert_eq!(game.adjacent_mines(4, 0), Some(1));
+
+        assert_eq!(game.adjacent_mines(0, 1), Some(2));
+        assert_eq!(game.adjacent_mines(4, 1), Some(2));
+
+        assert_eq!(game.adjacent_mines(0, 2), Some(3));
+        assert_eq!(game.adjacent_mines(2, 2), Some(8));
+        assert_eq!(game.adjacent_mines(4, 2), Some(3));
+
+        assert_eq!(game.adjacent_mines(0, 3), Some(2));
+        assert_eq!(game.adjacent_mines(4, 3), Some(2));
+
+        assert_eq!(game.adjacent_mines(0, 4), Some(1));
+        assert_eq!(game.adjacent_mines(1, 4), Some(2));
+        assert_eq!(game.adjacent_mines(2, 4), Some(3));
+        assert_eq!(game.adjacent_mines(3, 4), Some(2));
+        assert_eq!(game.adjacent_mines(4, 4), Some(1));
+
+        assert_eq!(game.adjacent_mines(0, 5), Some(0));
+        assert_eq!(game.adjacent_mines(1, 5), Some(0));
+        assert_eq!(game.adjacent_mines(2, 5), Some(0));
+        assert_eq!(game.adjacent_mines(3, 5), Some(0));
+        assert_eq!(game.adjacent_mines(4, 5), Some(0));
+
+        // Check out of bounds access
+        assert_eq!(game.adjacent_mines(W, H), None);
+        assert_eq!(game.adjacent_mines(H - 1, W - 1), None);
+    }
+
+    #[test]
+    fn params() {
+        let game = Game::with_mines(10, 8, 12);
+        let params = game.params();
+        assert_eq!(
+            params,
+            GameParams {
+                width: 10,
+                height: 8,
+                mines: 12
+            }
+        );
+
+        let similar = Game::from_params(params);
+        assert_eq!(similar.params(), params);
+        assert_eq!(similar.state(), GameState::Continue);
+        for y in 0..similar.height() {
+            for x in 0..similar.width() {
+                assert_eq!(similar.cell_state(x, y), Some(CellState::Covered));
+            }
+        }
+
+        // There's always a safe cell
+        assert_eq!(Game::with_mines(3, 3, 100).mines(), 8);
+    }
+
+    #[test]
+    fn with_config() {
+        let game = Game::with_config(30, 16, 99, 42);
+        assert_eq!(game.mines(), 99);
+        assert_eq!(Game::with_config(3, 3, 100, 42).mines(), 8);
+
+        let layout = |game: &Game| {
+            (0..game.height())
+                .flat_map(|y| (0..game.width()).map(move |x| (x, y)))
+                .map(|(x, y)| game.has_mine(x, y).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(layout(&game), layout(&Game::with_config(30, 16, 99, 42)));
+        assert_ne!(layout(&game), layout(&Game::with_config(30, 16, 99, 43)));
+    }
+
+    #[test]
+    fn win_condition() {
+        let mut cells = vec![GameCell::default(); 3 * 3];
+        cells[0].mine = true;
+        cells[8].mine = true;
+        let board = Game::from_cells(3, 3, cells).unwrap();
+        assert_eq!(board.win_condition(), WinCondition::OpenAllSafe);
+
+        // Opening all the safe cells wins by default...
+        let mut game = board.clone();
+        assert!(game.open(2, 0));
+        assert_eq!(game.state(), GameState::Continue);
+        assert!(game.open(0, 2));
+        assert_eq!(game.state(), GameState::Won);
+
+        // ...while flagging the mines doesn't
+        let mut game = board.clone();
+        game.flag(0, 0);
+        game.flag(2, 2);
+        assert_eq!(game.state(), GameState::Continue);
+        // Nor does flagging everything
+        let mut game = board.clone();
+        assert_eq!(game.flag_region(0, 0, 2, 2, true).len(), 9);
+        assert_eq!(game.state(), GameState::Continue);
+        for y in 0..3 {
+            for x in 0..3 {
+                game.flag(x, y);
+            }
+        }
+        assert_eq!(game.state(), GameState::Continue);
+
+        let mut game = board.clone();
+        game.set_win_condition(WinCondition::FlagAllMines);
+        // Opening everything isn't enough
+        assert!(game.open(2, 0));
+        assert!(game.open(0, 2));
+        assert_eq!(game.state(), GameState::Continue);
+        // Every mine has to be flagged
+        game.flag(0, 0);
+        assert_eq!(game.state(), GameState::Continue);
+        assert_eq!(game.flag(2, 2), Some(true));
+        assert_eq!(game.state(), GameState::Won);
+
+        // A wrong flag spoils it, until it's removed
+        let mut game = board.clone();
+        game.set_win_condition(WinCondition::FlagAllMines);
+        game.flag(1, 1);
+        assert_eq!(game.flag_region(0, 0, 2, 2, true).len(), 8);
+        assert_eq!(game.state(), GameState::Continue);
+        game.flag(1, 1);
+        assert_eq!(game.state(), GameState::Continue);
+        game.flag_region(0, 1, 2, 1, false);
+        game.flag_region(1, 0, 2, 0, false);
+        game.flag_region(0, 2, 1, 2, false);
+        assert_eq!(game.state(), GameState::Won);
+
+        // The condition survives a save
+        #[cfg(feature = "serde")]
+        let saved = Game::from_json(&game.to_json()).unwrap();
+        #[cfg(feature = "serde")]
+        assert_eq!(saved.win_condition(), WinCondition::FlagAllMines);
+    }
+
+    #[test]
+    fn bytes() {
+        // An expert board
+        let mut game = Game::with_mines(30, 16, 99);
+        game.set_question_marks(true);
+        game.set_win_condition(WinCondition::FlagAllMines);
+        let safe = game.cells.iter().position(|c| !c.mine).unwrap();
+        game.apply_move(Move::Open((safe % 30) as u8, (safe / 30) as u8));
+        game.flag_region(0, 0, 5, 5, true);
+        game.apply_move(Move::Flag(29, 15));
+        game.apply_move(Move::Flag(29, 15));
+        game.apply_move(Move::Chord((safe % 30) as u8, (safe / 30) as u8));
+        assert!(game.history().len() >= 3);
+
+        let bytes = game.to_bytes();
+        assert_eq!(Game::from_bytes(&bytes).unwrap(), game);
+        // 7 bytes of header, 120 for the states, 60 for the mines and 3 per move
+        assert_eq!(bytes.len(), 187 + 3 * game.history().len());
+        #[cfg(feature = "serde")]
+        assert!(bytes.len() * 10 < game.to_json().len());
+
+        // The game state is kept too
+        let mut cells = vec![GameCell::default(); 3];
+        cells[1].mine = true;
+        let mut lost = Game::from_cells(3, 1, cells).unwrap();
+        lost.open(0, 0);
+        lost.open(1, 0);
+        let loaded = Game::from_bytes(&lost.to_bytes()).unwrap();
+        assert_eq!(loaded.state(), GameState::Lost);
+        assert_eq!(loaded, lost);
+
+        // A flag taken back still started the game
+        let mut game = Game::with_mines(5, 5, 3);
+        game.apply_move(Move::Flag(0, 0));
+        game.apply_move(Move::Flag(0, 0));
+        assert!(game.has_started());
+        let loaded = Game::from_bytes(&game.to_bytes()).unwrap();
+        assert!(loaded.has_started());
+        assert_eq!(loaded, game);
+
+        assert!(matches!(
+            Game::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(LoadError::InvalidBoard)
+        ));
+        assert!(matches!(
+            Game::from_bytes(&[]),
+            Err(LoadError::InvalidBoard)
+        ));
+        let mut other = bytes.clone();
+        other[0] = BYTES_VERSION + 1;
+        assert!(matches!(
+            Game::from_bytes(&other),
+            Err(LoadError::UnsupportedVersion(3))
+        ));
+        // A move off the board
+        let mut off = bytes.clone();
+        let last = off.len() - 1;
+        off[last] = 16;
+        assert!(matches!(
+            Game::from_bytes(&off),
+            Err(LoadError::InvalidBoard)
+        ));
+        let mut wide = bytes;
+        wide[2] = 1;
+        assert!(matches!(
+            Game::from_bytes(&wide),
+            Err(LoadError::InvalidBoard)
+        ));
+    }
+
+    #[test]
+    fn difficulty() {
+        for &(level, size, mines) in &[
+            (DifficultyLevel::Easy, 5, 6),
+            (DifficultyLevel::Medium, 10, 25),
+            (DifficultyLevel::Hard, 15, 56),
+        ] {
+            let game = Game::from_difficulty(level);
+            assert_eq!(game.width(), size);
+            assert_eq!(game.height(), size);
+            assert_eq!(game.mines(), mines);
+            assert_eq!(game.params(), level.params());
+        }
+    }
+
+    #[test]
+    fn inspect() {
+        const N: u8 = 4;
+        let mut game = Game::new(N, N);
+
+        // x000
+        // 0x00
+        // 0000
+        // 0000
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(0, 0).unwrap().mine = true;
+        game.cell_mut(1, 1).unwrap().mine = true;
+        game.flag(1, 1);
+
+        for (x, y) in [(0, 1), (1, 1), (3, 3)] {
+            let info = game.inspect(x, y).unwrap();
+            assert_eq!(Some(info.state), game.cell_state(x, y));
+            assert_eq!(Some(info.mine), game.has_mine(x, y));
+            assert_eq!(Some(info.adjacent), game.adjacent_mines(x, y));
+            assert_eq!(info.neighbors, game.adj(x, y));
+        }
+        assert_eq!(
+            game.inspect(1, 1),
+            Some(CellInfo {
+                state: CellState::Flagged,
+                mine: true,
+                adjacent: 1,
+                neighbors: game.adj(1, 1),
+            })
+        );
+        assert_eq!(
+            game.dump(1, 1),
+            Some("Flagged, mine: true, adjacent mines: 1".to_string())
+        );
+
+        // Check out of bounds access
+        assert_eq!(game.inspect(N, N), None);
+        assert_eq!(game.dump(N, N), None);
+    }
+
+    #[test]
+    fn large_board() {
+        const N: u8 = 16;
+        let mut game = Game::new(N, N);
+
+        assert_eq!(game.cells.len(), 256);
+
+        game.cells = vec![GameCell::default(); 256];
+        game.cell_mut(N - 1, N - 1).unwrap().mine = true;
+        assert_eq!(game.has_mine(N - 1, N - 1), Some(true));
+        assert_eq!(game.adjacent_mines(N - 2, N - 2), Some(1));
+        assert_eq!(game.mines(), 1);
+    }
+
+    #[test]
+    fn visible_number() {
+        const N: u8 = 4;
+        let mut game = Game::new(N, N);
+
+        // x000
+        // 0000
+        // 0000
+        // 0000
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(0, 0).unwrap().mine = true;
+
+        assert_eq!(game.visible_number(1, 1), None);
+        assert_eq!(game.visible_number(3, 3), None);
+
+        game.open(3, 3);
+        assert_eq!(game.visible_number(1, 1), Some(1));
+        assert_eq!(game.visible_number(3, 3), Some(0));
+        // Mined, flagged and out of bounds cells have no visible number
+        assert_eq!(game.visible_number(0, 0), None);
+        game.flag(0, 0);
+        assert_eq!(game.visible_number(0, 0), None);
+        assert_eq!(game.visible_number(N, N), None);
+    }
+
+    #[test]
+    fn adjacent_flags() {
+        const H: u8 = 4;
+        const W: u8 = 5;
+        let mut game = Game::new(W, H);
+
+        // F000F
+        // 0F000
+        // 00000
+        // F0000
+        game.cells = vec![GameCell::default(); (H * W) as usize];
+        for y in 0..H {
+            for x in 0..W {
+                assert_eq!(game.adjacent_flags(x, y), Some(0));
+            }
+        }
+
+        game.flag(0, 0);
+        game.flag(4, 0);
+        game.flag(1, 1);
+        game.flag(0, 3);
+
+        assert_eq!(game.adjacent_flags(0, 0), Some(1));
+        assert_eq!(game.adjacent_flags(1, 0), Some(2));
+        assert_eq!(game.adjacent_flags(1, 1), Some(1));
+        assert_eq!(game.adjacent_flags(3, 0), Some(1));
+        assert_eq!(game.adjacent_flags(4, 1), Some(1));
+        assert_eq!(game.adjacent_flags(0, 2), Some(2));
+        assert_eq!(game.adjacent_flags(1, 3), Some(1));
+        assert_eq!(game.adjacent_flags(4, 3), Some(0));
+
+        // Questioned cells aren't flags
+        game.set_question_marks(true);
+        game.flag(1, 1);
+        assert_eq!(game.cell_state(1, 1), Some(CellState::Questioned));
+        assert_eq!(game.adjacent_flags(1, 0), Some(1));
+
+        // Check out of bounds access
+        assert_eq!(game.adjacent_flags(W, H), None);
+        assert_eq!(game.adjacent_flags(H - 1, W - 1), None);
+    }
+
+    #[test]
+    fn unflagged_mines() {
+        // *.*.
+        // ..*.
+        let mut cells = vec![GameCell::default(); 8];
+        cells[0].mine = true;
+        cells[2].mine = true;
+        cells[6].mine = true;
+        let mut game = Game::from_cells(4, 2, cells).unwrap();
+        assert_eq!(game.unflagged_mines(), 3);
+
+        // Correct flags
+        game.flag(0, 0);
+        game.flag(2, 1);
+        assert_eq!(game.unflagged_mines(), 1);
+        assert_eq!(game.mines() - game.flagged(), 1);
+
+        // Incorrect flags don't count
+        game.flag(1, 0);
+        assert_eq!(game.unflagged_mines(), 1);
+        assert_eq!(game.mines() - game.flagged(), 0);
+
+        // Only incorrect flags
+        game.flag(0, 0);
+        game.flag(2, 1);
+        assert_eq!(game.unflagged_mines(), 3);
+
+        game.flag(2, 0);
+        game.flag(0, 0);
+        game.flag(2, 1);
+        assert_eq!(game.unflagged_mines(), 0);
+    }
+
+    #[test]
+    fn remaining_mines_around() {
+        // **.
+        // ...
+        // ...
+        let mut cells = vec![GameCell::default(); 9];
+        cells[0].mine = true;
+        cells[1].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        game.open(1, 1);
+        game.open(2, 1);
+
+        // Covered and out of bounds cells
+        assert_eq!(game.remaining_mines_around(0, 0), None);
+        assert_eq!(game.remaining_mines_around(2, 2), None);
+        assert_eq!(game.remaining_mines_around(3, 3), None);
+
+        // Unsatisfied
+        assert_eq!(game.remaining_mines_around(1, 1), Some(2));
+        game.flag(0, 0);
+        assert_eq!(game.remaining_mines_around(1, 1), Some(1));
+        assert_eq!(game.remaining_mines_around(2, 1), Some(1));
+
+        // Satisfied
+        game.flag(1, 0);
+        assert_eq!(game.remaining_mines_around(1, 1), Some(0));
+        assert_eq!(game.remaining_mines_around(2, 1), Some(0));
+
+        // Over-flagged
+        game.flag(0, 1);
+        assert_eq!(game.remaining_mines_around(1, 1), Some(-1));
+        assert_eq!(game.remaining_mines_around(2, 1), Some(0));
+    }
+
+    #[test]
+    fn auto_complete() {
+        // UUU
+        // U*U
+        // *F*
+        let mut cells = vec![
+            GameCell {
+                state: CellState::Uncovered,
+                mine: false,
+            };
+            9
+        ];
+        for &idx in [4, 6, 7, 8].iter() {
+            cells[idx] = GameCell::default();
+        }
+        cells[4].mine = true;
+        cells[6].mine = true;
+        cells[8].mine = true;
+        cells[7].state = CellState::Flagged;
+
+        // One of the remaining cells is safe: nothing is certain
+        let mut game = Game::from_cells(3, 3, cells.clone()).unwrap();
+        assert!(!game.try_auto_complete());
+        assert_eq!(game.state(), GameState::Continue);
+        assert_eq!(game.cell_state(1, 1), Some(CellState::Covered));
+
+        // Every remaining cell is a mine, even the flagged one
+        cells[7].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        assert!(game.try_auto_complete());
+        assert_eq!(game.state(), GameState::Won);
+        assert_eq!(game.flagged(), 4);
+        assert_eq!(game.unflagged_mines(), 0);
+        assert!(!game.try_auto_complete());
+
+        // Winning by opening the last safe cell flags the rest
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        game.open(1, 0);
+        game.open(0, 1);
+        game.open(1, 1);
+        assert_eq!(game.state(), GameState::Won);
+        assert!(game.try_auto_complete());
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Flagged));
+
+        // Flagging wins the game only once the mines are flagged
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        game.set_win_condition(WinCondition::FlagAllMines);
+        game.open(1, 0);
+        game.open(0, 1);
+        game.open(1, 1);
+        assert_eq!(game.state(), GameState::Continue);
+        assert!(game.try_auto_complete());
+        assert_eq!(game.state(), GameState::Won);
+        // The flags are moves, so a replay makes them too
+        assert_eq!(game.history(), [Move::Flag(0, 0)]);
+
+        // Lost games stay lost
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        game.open(1, 1);
+        game.open(0, 0);
+        assert!(!game.try_auto_complete());
+        assert_eq!(game.state(), GameState::Lost);
+    }
+
+    #[test]
+    fn open_regions() {
+        // ..*..
+        // ..*..
+        // ..*..
+        let mut cells = vec![GameCell::default(); 15];
+        for &idx in [2, 7, 12].iter() {
+            cells[idx].mine = true;
+        }
+        let game = Game::from_cells(5, 3, cells).unwrap();
+
+        let regions = game.open_regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(
+            regions[0],
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)]
+        );
+        assert_eq!(
+            regions[1],
+            vec![(3, 0), (4, 0), (3, 1), (4, 1), (3, 2), (4, 2)]
+        );
+
+        // Opening any empty cell of a region opens exactly that region
+        for region in regions {
+            let mut game = Game::from_cells(5, 3, game.cells.clone()).unwrap();
+            let &(x, y) = region
+                .iter()
+                .find(|(x, y)| game.adjacent_mines(*x, *y) == Some(0))
+                .unwrap();
+            game.open(x, y);
+            for y in 0..3 {
+                for x in 0..5 {
+                    assert_eq!(
+                        game.cell_state(x, y) == Some(CellState::Uncovered),
+                        region.contains(&(x, y))
+                    );
+                }
+            }
         }
-        write!(f, "\n")
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // No empty cells - no regions
+        let game = Game::from_cells(
+            2,
+            1,
+            vec![
+                GameCell::default(),
+                GameCell {
+                    mine: true,
+                    ..Default::default()
+                },
+            ],
+        )
+        .unwrap();
+        assert!(game.open_regions().is_empty());
+    }
 
     #[test]
-    fn getters() {
-        const H: u8 = 6;
-        const W: u8 = 5;
-        let mut game = Game::new(W, H);
-
-        assert_eq!(game.height(), H);
-        assert_eq!(game.width(), W);
-        assert_eq!(game.state(), GameState::Continue);
+    fn analyze() {
+        // *1 1F1.
+        let mut cells = vec![GameCell::default(); 6];
+        cells[0].mine = true;
+        cells[3].mine = true;
+        cells[3].state = CellState::Flagged;
+        for &idx in [1, 2, 4].iter() {
+            cells[idx].state = CellState::Uncovered;
+        }
+        let game = Game::from_cells(6, 1, cells).unwrap();
 
-        // Cell state
-        // After initialization all cells should be covered
-        for y in 0..H {
-            for x in 0..W {
-                assert_eq!(game.cell_state(x, y), Some(CellState::Covered));
+        assert_eq!(
+            game.analyze(),
+            Analysis {
+                forced_mines: vec![(0, 0)],
+                forced_safes: vec![(5, 0)],
             }
-        }
-        // Check out of bounds access
-        assert_eq!(game.cell_state(W, H), None);
-        assert_eq!(game.cell_state(H - 1, W - 1), None);
+        );
 
-        // Flagged
-        game.cells = vec![GameCell::default(); (H * W) as usize];
-        game.cell_mut(0, 0).unwrap().state = CellState::Flagged;
-        game.cell_mut(W - 1, H - 1).unwrap().state = CellState::Flagged;
-        assert_eq!(game.flagged(), 2);
+        // Nothing is known before the first click
+        let game = Game::with_mines(5, 5, 5);
+        assert_eq!(game.analyze(), Analysis::default());
+    }
 
-        // Mines
-        // Place a mine into the first cell and check that its reflected correctly
-        game.cell_mut(0, 0).unwrap().mine = true;
-        assert_eq!(game.has_mine(0, 0), Some(true));
-        // Check out of bounds access
-        assert_eq!(game.has_mine(W, H), None);
-        assert_eq!(game.has_mine(H - 1, W - 1), None);
-        // Check adjacent mines
-        game.cells = vec![GameCell::default(); (H * W) as usize];
-        // No mines at all
-        for y in 0..H {
-            for x in 0..W {
-                assert_eq!(game.adjacent_mines(x, y), Some(0));
+    #[test]
+    fn flood_from() {
+        // ..*..
+        // .....
+        // .....
+        // ....*
+        let mut cells = vec![GameCell::default(); 20];
+        cells[2].mine = true;
+        cells[19].mine = true;
+        let mut game = Game::from_cells(5, 4, cells).unwrap();
+        game.flag(2, 2);
+
+        assert!(game.flood_from(2, 0).is_empty());
+        assert!(game.flood_from(2, 2).is_empty());
+        assert!(game.flood_from(5, 0).is_empty());
+        assert_eq!(game.flood_from(1, 0), vec![(1, 0)]);
+
+        let mut region = game.flood_from(0, 3);
+        region.sort_by_key(|&(x, y)| (y, x));
+        let before = game.clone();
+        game.open(0, 3);
+        let mut opened = vec![];
+        for y in 0..4 {
+            for x in 0..5 {
+                if game.cell_state(x, y) != before.cell_state(x, y) {
+                    opened.push((x, y));
+                }
             }
         }
-        // 12321
-        // 2xxx2
-        // 3x8x3
-        // 2xxx2
-        // 12321
-        // 00000
-        game.cell_mut(1, 1).unwrap().mine = true;
-        game.cell_mut(2, 1).unwrap().mine = true;
-        game.cell_mut(3, 1).unwrap().mine = true;
-        game.cell_mut(1, 2).unwrap().mine = true;
-        game.cell_mut(3, 2).unwrap().mine = true;
-        game.cell_mut(1, 3).unwrap().mine = true;
-        game.cell_mut(2, 3).unwrap().mine = true;
-        game.cell_mut(3, 3).unwrap().mine = true;
+        assert_eq!(region, opened);
+        // The flag stops the cascade
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Flagged));
+        assert!(game.flood_from(0, 3).is_empty());
+    }
 
-        assert_eq!(game.adjacent_mines(0, 0), Some(1));
-        assert_eq!(game.adjacent_mines(1, 0), Some(2));
-        assert_eq!(game.adjacent_mines(2, 0), Some(3));
-        assert_eq!(game.adjacent_mines(3, 0), Some(2));
-        assert_eq!(game.adjacent_mines(4, 0), Some(1));
+    #[test]
+    fn has_started() {
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let game = Game::from_cells(2, 2, cells.clone()).unwrap();
+        assert!(!game.has_started());
+
+        let mut game = Game::from_cells(2, 2, cells.clone()).unwrap();
+        game.flag(1, 1);
+        assert!(game.has_started());
+        // Removing the only flag doesn't stop the game
+        game.flag(1, 1);
+        assert!(game.has_started());
+
+        let mut game = Game::from_cells(2, 2, cells.clone()).unwrap();
+        game.open(1, 1);
+        assert!(game.has_started());
+
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        game.open(0, 0);
+        assert!(game.has_started());
+    }
 
-        assert_eq!(game.adjacent_mines(0, 1), Some(2));
-        assert_eq!(game.adjacent_mines(4, 1), Some(2));
+    #[test]
+    fn state_predicates() {
+        let mut cells = vec![GameCell::default(); 2];
+        cells[0].mine = true;
+
+        let game = Game::from_cells(2, 1, cells.clone()).unwrap();
+        assert!(!game.is_won());
+        assert!(!game.is_lost());
+        assert!(!game.is_over());
+
+        let mut game = Game::from_cells(2, 1, cells.clone()).unwrap();
+        game.open(1, 0);
+        assert!(game.is_won());
+        assert!(!game.is_lost());
+        assert!(game.is_over());
+
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        game.open(1, 1);
+        game.open(0, 0);
+        assert!(!game.is_won());
+        assert!(game.is_lost());
+        assert!(game.is_over());
+    }
 
-        assert_eq!(game.adjacent_mines(0, 2), Some(3));
-        assert_eq!(game.adjacent_mines(2, 2), Some(8));
-        assert_eq!(game.adjacent_mines(4, 2), Some(3));
+    #[test]
+    fn frontier() {
+        const H: u8 = 4;
+        const W: u8 = 5;
+        let mut game = Game::from_cells(W, H, vec![GameCell::default(); (H * W) as usize]).unwrap();
 
-        assert_eq!(game.adjacent_mines(0, 3), Some(2));
-        assert_eq!(game.adjacent_mines(4, 3), Some(2));
+        // Nothing is uncovered yet
+        for y in 0..H {
+            for x in 0..W {
+                assert!(!game.is_frontier(x, y));
+            }
+        }
 
-        assert_eq!(game.adjacent_mines(0, 4), Some(1));
-        assert_eq!(game.adjacent_mines(1, 4), Some(2));
-        assert_eq!(game.adjacent_mines(2, 4), Some(3));
-        assert_eq!(game.adjacent_mines(3, 4), Some(2));
-        assert_eq!(game.adjacent_mines(4, 4), Some(1));
+        // U.... U - uncovered, F - flagged, ? - questioned
+        // UF...
+        // U?...
+        // U....
+        for y in 0..H {
+            game.cells[(y * W) as usize].state = CellState::Uncovered;
+        }
+        game.flag(1, 1);
+        game.set_question_marks(true);
+        game.flag(1, 2);
+        game.flag(1, 2);
+        assert_eq!(game.cell_state(1, 2), Some(CellState::Questioned));
 
-        assert_eq!(game.adjacent_mines(0, 5), Some(0));
-        assert_eq!(game.adjacent_mines(1, 5), Some(0));
-        assert_eq!(game.adjacent_mines(2, 5), Some(0));
-        assert_eq!(game.adjacent_mines(3, 5), Some(0));
-        assert_eq!(game.adjacent_mines(4, 5), Some(0));
+        for y in 0..H {
+            assert!(!game.is_frontier(0, y));
+            for x in 2..W {
+                assert!(!game.is_frontier(x, y));
+            }
+        }
+        assert!(game.is_frontier(1, 0));
+        assert!(!game.is_frontier(1, 1));
+        assert!(game.is_frontier(1, 2));
+        assert!(game.is_frontier(1, 3));
 
         // Check out of bounds access
-        assert_eq!(game.adjacent_mines(W, H), None);
-        assert_eq!(game.adjacent_mines(H - 1, W - 1), None);
+        assert!(!game.is_frontier(W, H));
     }
 
     #[test]
@@ -436,6 +2301,331 @@ mod tests {
         assert_eq!(game.cell_state(2, 3), Some(CellState::Uncovered));
     }
 
+    #[test]
+    fn open_no_op() {
+        const N: u8 = 4;
+        let mut game = Game::new(N, N);
+
+        // 0000
+        // 0x00
+        // 0000
+        // 0000
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(1, 1).unwrap().mine = true;
+
+        assert!(game.open(0, 0));
+        let cells = game.cells.clone();
+
+        // Opening an already open cell changes nothing
+        assert!(!game.open(0, 0));
+        assert_eq!(game.cells, cells);
+        assert_eq!(game.state(), GameState::Continue);
+
+        // Neither does opening a flagged one, even if it's mined
+        game.flag(1, 1);
+        let cells = game.cells.clone();
+        assert!(!game.open(1, 1));
+        assert_eq!(game.cells, cells);
+        assert_eq!(game.state(), GameState::Continue);
+
+        // Out of bounds
+        assert!(!game.open(N, N));
+    }
+
+    #[test]
+    fn hint() {
+        const N: u8 = 4;
+        let mut game = Game::new(N, N);
+
+        // xx00
+        // xx00
+        // 0000
+        // 0000
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(0, 0).unwrap().mine = true;
+        game.cell_mut(1, 0).unwrap().mine = true;
+        game.cell_mut(0, 1).unwrap().mine = true;
+        game.cell_mut(1, 1).unwrap().mine = true;
+
+        let (x, y) = game.hint().unwrap();
+        assert_eq!(game.has_mine(x, y), Some(false));
+        assert_eq!(game.cell_state(x, y), Some(CellState::Uncovered));
+
+        // Keep hinting until there are no safe cells left
+        while game.hint().is_some() {}
+        assert_eq!(game.state(), GameState::Won);
+        assert_eq!(game.hint(), None);
+    }
+
+    #[test]
+    fn open_region() {
+        const N: u8 = 6;
+        let mut game = Game::new(N, N);
+
+        // 000000
+        // 000000
+        // 000000
+        // 000011
+        // 00001x
+        // 0000xx
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(5, 4).unwrap().mine = true;
+        game.cell_mut(4, 5).unwrap().mine = true;
+        game.cell_mut(5, 5).unwrap().mine = true;
+        game.cell_mut(4, 4).unwrap().state = CellState::Uncovered;
+
+        // Only numbers in the region, so nothing cascades beyond it
+        let opened = game.open_region(4, 3, 5, 3);
+        assert_eq!(opened, vec![(4, 3), (5, 3)]);
+        assert_eq!(game.state(), GameState::Continue);
+
+        let opened = game.open_region(1, 1, 2, 2);
+        for y in 1..=2 {
+            for x in 1..=2 {
+                assert_eq!(game.cell_state(x, y), Some(CellState::Uncovered));
+                assert!(opened.contains(&(x, y)));
+            }
+        }
+        // Already uncovered cells weren't newly opened
+        assert!(!opened.contains(&(4, 4)));
+        assert!(!opened.contains(&(4, 3)));
+        assert_eq!(game.state(), GameState::Won);
+
+        // A mine in the region loses the game, the rectangle is clamped
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(5, 5).unwrap().mine = true;
+        game.state = GameState::Continue;
+        let opened = game.open_region(4, 4, 200, 200);
+        assert_eq!(opened, vec![(4, 4), (5, 4), (4, 5)]);
+        assert_eq!(game.state(), GameState::Lost);
+    }
+
+    #[test]
+    fn safe_cell() {
+        struct AllMines;
+        impl BoardGenerator for AllMines {
+            fn generate(&self, w: u8, h: u8, _mines: usize) -> Vec<GameCell> {
+                vec![
+                    GameCell {
+                        state: CellState::Covered,
+                        mine: true,
+                    };
+                    w as usize * h as usize
+                ]
+            }
+        }
+
+        // Generated boards are adjusted
+        for game in [
+            Game::with_mines(4, 4, 16),
+            Game::generate(4, 4, 16, &AllMines),
+            Game::generate(1, 1, 1, &RandomGenerator),
+        ]
+        .iter()
+        {
+            assert!(game.has_safe_cell());
+            assert_eq!(
+                game.mines(),
+                game.width() as usize * game.height() as usize - 1
+            );
+        }
+        // Symmetry can leave more than one safe cell
+        let game = Game::generate(
+            3,
+            3,
+            9,
+            &SymmetryGenerator {
+                kind: SymmetryKind::Rotational,
+            },
+        );
+        assert!(game.has_safe_cell());
+
+        // Exact boards are rejected
+        let mines = vec![
+            GameCell {
+                state: CellState::Covered,
+                mine: true
+            };
+            4
+        ];
+        assert!(Game::from_cells(2, 2, mines.clone()).is_none());
+        let mut cells = mines;
+        cells[3].mine = false;
+        assert!(Game::from_cells(2, 2, cells).unwrap().has_safe_cell());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_game() {
+        let mut cells = vec![GameCell::default(); 6];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(3, 2, cells).unwrap();
+        game.set_question_marks(true);
+        game.open(2, 1);
+        game.flag(1, 0);
+        game.flag(1, 0);
+
+        let json = game.to_json();
+        assert_eq!(Game::from_json(&json).unwrap(), game);
+
+        // Future versions are rejected, whatever they look like
+        let future = json.replace(
+            &format!("\"version\":{}", SAVE_VERSION),
+            &format!("\"version\":{}", SAVE_VERSION + 1),
+        );
+        assert_ne!(future, json);
+        assert!(matches!(
+            Game::from_json(&future),
+            Err(LoadError::UnsupportedVersion(v)) if v == SAVE_VERSION + 1
+        ));
+        assert!(matches!(
+            Game::from_json(r#"{"version":2,"board":"something new"}"#),
+            Err(LoadError::UnsupportedVersion(2))
+        ));
+
+        // Not a saved game
+        assert!(matches!(Game::from_json("{}"), Err(LoadError::Json(_))));
+        assert!(matches!(
+            Game::from_json(r#"{"version":1}"#),
+            Err(LoadError::Json(_))
+        ));
+
+        // Inconsistent board
+        let broken = json.replace("\"w\":3", "\"w\":4");
+        assert!(matches!(
+            Game::from_json(&broken),
+            Err(LoadError::InvalidBoard)
+        ));
+    }
+
+    #[test]
+    fn share() {
+        let mut game = Game::with_mines(13, 7, 20);
+        let initial = game.clone();
+        let code = encode_share(&game, &[]);
+        // A bit per cell: 3 + 12 bytes
+        assert_eq!(code.len(), 20);
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        // The progress isn't shared, only the moves
+        let safe = game.cells.iter().position(|c| !c.mine).unwrap();
+        let moves = [
+            Move::Open((safe % 13) as u8, (safe / 13) as u8),
+            Move::Flag(1, 1),
+        ];
+        for m in moves.iter() {
+            game.apply_move(*m);
+        }
+        assert_eq!(encode_share(&initial, &[]), code);
+
+        let (shared, shared_moves) = decode_share(&code).unwrap();
+        assert!(shared_moves.is_empty());
+        assert_eq!(shared.params(), game.params());
+        for y in 0..7 {
+            for x in 0..13 {
+                assert_eq!(shared.has_mine(x, y), initial.has_mine(x, y));
+                assert_eq!(shared.cell_state(x, y), Some(CellState::Covered));
+            }
+        }
+
+        // Three bytes per move: 3 + 12 + 6 bytes
+        let with_moves = encode_share(&initial, &moves);
+        assert_eq!(with_moves.len(), 28);
+        let (mut replayed, shared_moves) = decode_share(&with_moves).unwrap();
+        assert_eq!(shared_moves, moves);
+        for m in shared_moves {
+            replayed.apply_move(m);
+        }
+        assert_eq!(replayed.to_string(), game.to_string());
+
+        assert!(matches!(
+            decode_share("not a code!"),
+            Err(LoadError::Encoding(_))
+        ));
+        assert!(matches!(decode_share(""), Err(LoadError::InvalidBoard)));
+        // Truncated
+        assert!(matches!(
+            decode_share(&code[..code.len() - 4]),
+            Err(LoadError::InvalidBoard)
+        ));
+        assert!(matches!(
+            decode_share(&with_moves[..with_moves.len() - 1]),
+            Err(LoadError::InvalidBoard)
+        ));
+        // Unknown move and a move off the board
+        let mut bytes = base64::decode_config(&with_moves, base64::URL_SAFE_NO_PAD).unwrap();
+        bytes[15] = 3;
+        let unknown = base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD);
+        assert!(matches!(
+            decode_share(&unknown),
+            Err(LoadError::InvalidBoard)
+        ));
+        bytes[15] = 0;
+        bytes[16] = 13;
+        let outside = base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD);
+        assert!(matches!(
+            decode_share(&outside),
+            Err(LoadError::InvalidBoard)
+        ));
+        // Unknown version
+        let mut bytes = base64::decode_config(&code, base64::URL_SAFE_NO_PAD).unwrap();
+        bytes[0] = SHARE_VERSION + 1;
+        let future = base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        assert!(matches!(
+            decode_share(&future),
+            Err(LoadError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn flag_region() {
+        // ...*
+        // .*..
+        // ....
+        let mut cells = vec![GameCell::default(); 12];
+        cells[3].mine = true;
+        cells[5].mine = true;
+        let mut game = Game::from_cells(4, 3, cells).unwrap();
+        game.open(3, 2);
+        game.set_question_marks(true);
+        game.flag(2, 0);
+        game.flag(2, 0);
+        assert_eq!(game.cell_state(2, 0), Some(CellState::Questioned));
+        let uncovered: Vec<_> = (0..3)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|(x, y)| game.cell_state(*x, *y) == Some(CellState::Uncovered))
+            .collect();
+        assert_eq!(uncovered, vec![(2, 1), (3, 1), (2, 2), (3, 2)]);
+
+        // Clamped to the board, uncovered cells are left alone
+        let flagged = game.flag_region(1, 0, 200, 1, true);
+        for y in 0..=1 {
+            for x in 1..4 {
+                let expected = if uncovered.contains(&(x, y)) {
+                    CellState::Uncovered
+                } else {
+                    CellState::Flagged
+                };
+                assert_eq!(game.cell_state(x, y), Some(expected));
+                assert_eq!(flagged.contains(&(x, y)), expected == CellState::Flagged);
+            }
+        }
+        assert!(flagged.contains(&(2, 0)));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Covered));
+        assert!(game.flag_region(1, 0, 3, 1, true).is_empty());
+
+        // Unflagging, with the corners swapped
+        let unflagged = game.flag_region(3, 1, 2, 0, false);
+        assert_eq!(game.cell_state(1, 1), Some(CellState::Flagged));
+        for &(x, y) in unflagged.iter() {
+            assert!(x >= 2 && y <= 1);
+            assert_eq!(game.cell_state(x, y), Some(CellState::Covered));
+        }
+        assert!(unflagged.contains(&(3, 0)));
+    }
+
     #[test]
     fn flag() {
         const H: u8 = 6;
@@ -446,4 +2636,370 @@ mod tests {
         assert_eq!(game.flagged(), 1);
         assert_eq!(game.flag(0, 0), Some(false));
     }
+
+    #[test]
+    fn chord() {
+        const N: u8 = 4;
+        let mut game = Game::new(N, N);
+
+        // x100
+        // 1100
+        // 0000
+        // 0000
+        game.cells = vec![GameCell::default(); (N * N) as usize];
+        game.cell_mut(0, 0).unwrap().mine = true;
+        game.cell_mut(1, 1).unwrap().state = CellState::Uncovered;
+
+        // Not satisfied - nothing happens
+        game.chord(1, 1);
+        assert!(!game.is_satisfied(1, 1));
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Covered));
+
+        game.flag(0, 0);
+        assert!(game.is_satisfied(1, 1));
+        game.chord(1, 1);
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Flagged));
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Uncovered));
+        assert_eq!(game.state(), GameState::Won);
+
+        // .1x
+        // .1.
+        // ...
+        let mut cells = vec![GameCell::default(); 9];
+        cells[2].mine = true;
+        cells[3].state = CellState::Uncovered;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        // Without adjacent mines there's nothing to chord
+        assert!(!game.chord(0, 1));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Covered));
+
+        // A wrong flag detonates the mine
+        game.open(1, 1);
+        game.flag(1, 2);
+        assert!(game.chord(1, 1));
+        assert_eq!(game.state(), GameState::Lost);
+    }
+
+    #[test]
+    fn symmetry() {
+        let kinds = [
+            SymmetryKind::Horizontal,
+            SymmetryKind::Vertical,
+            SymmetryKind::Rotational,
+        ];
+        for &kind in kinds.iter() {
+            for &(w, h) in [(5, 5), (6, 4), (7, 10)].iter() {
+                let game = Game::generate(w, h, 12, &SymmetryGenerator { kind });
+                assert!(game.mines() <= 12);
+                assert!(game.mines() >= 11);
+
+                for y in 0..h {
+                    for x in 0..w {
+                        let (mx, my) = kind.mirror(w, h, x, y);
+                        assert_eq!(game.has_mine(x, y), game.has_mine(mx, my));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flag_counts() {
+        let mut cells = vec![GameCell::default(); 9];
+        cells[0].mine = true;
+        cells[8].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        game.set_question_marks(true);
+        assert_eq!(game.covered_count(), 9);
+        assert_eq!(game.correctly_flagged(), 0);
+
+        game.flag(0, 0);
+        game.flag(1, 0);
+        game.flag(2, 0);
+        game.flag(2, 0);
+        assert_eq!(game.flagged(), 2);
+        assert_eq!(game.correctly_flagged(), 1);
+        // Question marks are still covered
+        assert_eq!(game.covered_count(), 7);
+
+        game.open(1, 1);
+        assert_eq!(game.covered_count(), 6);
+    }
+
+    #[test]
+    fn flag_all_mines() {
+        let mut cells = vec![GameCell::default(); 9];
+        cells[0].mine = true;
+        cells[4].mine = true;
+        cells[8].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        game.set_question_marks(true);
+        game.flag(0, 0);
+        game.flag(1, 1);
+        game.flag(1, 1);
+        game.flag(1, 0);
+
+        assert!(game.flag_all_mines());
+        assert_eq!(game.flagged(), 4);
+        assert_eq!(game.unflagged_mines(), 0);
+        // Safe cells stay as they were
+        assert_eq!(game.cell_state(1, 0), Some(CellState::Flagged));
+        assert_eq!(game.cell_state(2, 0), Some(CellState::Covered));
+        assert_eq!(game.state(), GameState::Continue);
+        assert!(!game.flag_all_mines());
+    }
+
+    #[test]
+    fn apply_move() {
+        // *..
+        // ...
+        // ..*
+        let mut cells = vec![GameCell::default(); 9];
+        cells[0].mine = true;
+        cells[8].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+
+        assert_eq!(game.apply_move(Move::Open(1, 1)), MoveResult::Changed);
+        assert_eq!(game.cell_state(1, 1), Some(CellState::Uncovered));
+        assert_eq!(game.apply_move(Move::Open(1, 1)), MoveResult::Ignored);
+        assert_eq!(game.apply_move(Move::Open(3, 3)), MoveResult::Ignored);
+
+        assert_eq!(game.apply_move(Move::Flag(0, 0)), MoveResult::Changed);
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Flagged));
+        assert_eq!(game.apply_move(Move::Flag(1, 1)), MoveResult::Ignored);
+
+        // Not satisfied yet
+        assert_eq!(game.apply_move(Move::Chord(1, 1)), MoveResult::Ignored);
+        assert_eq!(game.apply_move(Move::Flag(2, 2)), MoveResult::Changed);
+        assert_eq!(
+            game.apply_move(Move::Chord(1, 1)),
+            MoveResult::Over(GameState::Won)
+        );
+
+        // Nothing goes once the game is over
+        assert_eq!(game.apply_move(Move::Flag(2, 2)), MoveResult::Ignored);
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Flagged));
+        // Only the moves that changed the board are kept
+        assert_eq!(
+            game.history(),
+            [
+                Move::Open(1, 1),
+                Move::Flag(0, 0),
+                Move::Flag(2, 2),
+                Move::Chord(1, 1)
+            ]
+        );
+
+        let mut cells = vec![GameCell::default(); 4];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 2, cells).unwrap();
+        assert_eq!(game.apply_move(Move::Open(1, 1)), MoveResult::Changed);
+        assert_eq!(
+            game.apply_move(Move::Open(0, 0)),
+            MoveResult::Over(GameState::Lost)
+        );
+        assert!(!game.apply_move(Move::Open(1, 0)).changed());
+        assert_eq!(game.cell_state(1, 0), Some(CellState::Covered));
+        // Hints are moves too
+        let mut game = Game::from_cells(2, 2, vec![GameCell::default(); 4]).unwrap();
+        let (x, y) = game.hint().unwrap();
+        assert_eq!(game.history(), [Move::Open(x, y)]);
+    }
+
+    #[test]
+    fn first_click() {
+        let mut cells = vec![GameCell::default(); 5 * 5];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(5, 5, cells).unwrap();
+
+        assert!(game.open(0, 0));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Uncovered));
+        // The mine moved away from the cell and its neighbors
+        assert_eq!(game.mines(), 1);
+        assert_eq!(game.adjacent_mines(0, 0), Some(0));
+        for &(x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+            assert_eq!(game.has_mine(x, y), Some(false));
+        }
+        // To the same cell on every build, the cascade opened the rest
+        assert_eq!(game.has_mine(4, 2), Some(true));
+        assert!(game.is_won());
+
+        // Only the first open is safe
+        let mut cells = vec![GameCell::default(); 5 * 5];
+        cells[0].mine = true;
+        cells[2].mine = true;
+        let mut game = Game::from_cells(5, 5, cells).unwrap();
+        assert!(game.open(4, 4));
+        assert_eq!(game.state(), GameState::Continue);
+        assert!(game.open(0, 0));
+        assert!(game.is_lost());
+
+        // Flags and question marks keep their cells, the mine goes elsewhere
+        let mut cells = vec![GameCell::default(); 3];
+        cells[0].mine = true;
+        cells[1].state = CellState::Flagged;
+        let mut game = Game::from_cells(3, 1, cells).unwrap();
+        assert!(game.open(0, 0));
+        assert_eq!(game.has_mine(1, 0), Some(false));
+        assert_eq!(game.has_mine(2, 0), Some(true));
+
+        // With no room away from the neighbors, a neighbor takes the mine
+        let mut cells = vec![GameCell::default(); 2];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(2, 1, cells).unwrap();
+        assert!(game.open(0, 0));
+        assert!(game.is_won());
+        assert_eq!(game.has_mine(1, 0), Some(true));
+    }
+
+    #[test]
+    fn mulligan() {
+        let mut cells = vec![GameCell::default(); 5 * 5];
+        cells[0].mine = true;
+        cells[24].mine = true;
+        let board = Game::from_cells(5, 5, cells).unwrap();
+
+        // Off by default
+        let mut game = board.clone();
+        game.open(1, 1);
+        assert!(game.open(0, 0));
+        assert!(game.is_lost());
+
+        let mut game = board.clone();
+        game.set_mulligan(true);
+        game.open(1, 1);
+        assert!(game.open(0, 0));
+        assert_eq!(game.state(), GameState::Continue);
+        assert!(game.mulligan_used());
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Uncovered));
+        // The mine just moved somewhere else
+        assert_eq!(game.mines(), 2);
+        assert!(!game.cell(0, 0).unwrap().mine);
+        assert!(game.cell(4, 4).unwrap().mine);
+
+        // The same moves relocate the mine the same way
+        let mut again = board.clone();
+        again.set_mulligan(true);
+        again.open(1, 1);
+        again.open(0, 0);
+        assert_eq!(again, game);
+
+        // There's only one mulligan per game
+        assert!(game.open(4, 4));
+        assert!(game.is_lost());
+        assert!(Game::from_bytes(&game.to_bytes()).unwrap().mulligan_used());
+    }
+
+    #[test]
+    fn filtered_neighbors() {
+        // F?1
+        // ..*
+        // F..
+        let mut cells = vec![GameCell::default(); 9];
+        cells[5].mine = true;
+        cells[6].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        game.set_question_marks(true);
+        game.flag(0, 0);
+        game.flag(1, 0);
+        game.flag(1, 0);
+        game.open(2, 0);
+        game.flag(0, 2);
+
+        let covered: Vec<_> = game.covered_neighbors(1, 1).collect();
+        assert_eq!(covered, [(1, 0), (0, 1), (2, 1), (1, 2), (2, 2)]);
+        let flagged: Vec<_> = game.flagged_neighbors(1, 1).collect();
+        assert_eq!(flagged, [(0, 0), (0, 2)]);
+
+        // The cell itself doesn't count
+        let covered: Vec<_> = game.covered_neighbors(0, 1).collect();
+        assert_eq!(covered, [(1, 0), (1, 1), (1, 2)]);
+        let flagged: Vec<_> = game.flagged_neighbors(2, 2).collect();
+        assert!(flagged.is_empty());
+
+        assert_eq!(game.covered_neighbors(3, 0).count(), 0);
+        assert_eq!(game.flagged_neighbors(0, 3).count(), 0);
+        assert_eq!(game.flagged_neighbors(200, 200).count(), 0);
+    }
+
+    #[test]
+    fn chord_preview() {
+        // *..
+        // ...
+        // ...
+        let mut cells = vec![GameCell::default(); 9];
+        cells[0].mine = true;
+        let mut game = Game::from_cells(3, 3, cells).unwrap();
+        game.open(1, 1);
+
+        // Covered and unsatisfied cells
+        assert!(game.chord_preview(0, 1).is_empty());
+        assert!(game.chord_preview(1, 1).is_empty());
+        assert!(game.chord_preview(3, 3).is_empty());
+
+        // Satisfied: every covered neighbor but the flag
+        game.set_question_marks(true);
+        game.flag(0, 0);
+        game.flag(2, 2);
+        game.flag(2, 2);
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Questioned));
+        let preview = game.chord_preview(1, 1);
+        assert_eq!(
+            preview,
+            vec![(1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+        // Nothing changes
+        assert_eq!(game.cell_state(1, 0), Some(CellState::Covered));
+
+        // Over-flagged
+        game.flag(2, 2);
+        game.flag(2, 2);
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Flagged));
+        assert!(game.chord_preview(1, 1).is_empty());
+
+        // Preview matches what a chord does
+        game.flag(2, 2);
+        game.flag(2, 2);
+        assert_eq!(game.cell_state(2, 2), Some(CellState::Covered));
+        let preview = game.chord_preview(1, 1);
+        assert!(game.chord(1, 1));
+        for y in 0..3 {
+            for x in 0..3 {
+                assert_eq!(
+                    game.cell_state(x, y) == Some(CellState::Uncovered),
+                    (x, y) == (1, 1) || preview.contains(&(x, y))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn question_marks() {
+        const H: u8 = 6;
+        const W: u8 = 5;
+        let mut game = Game::new(W, H);
+
+        // Disabled by default: covered <-> flagged
+        assert_eq!(game.flag(0, 0), Some(true));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Flagged));
+        assert_eq!(game.flag(0, 0), Some(false));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Covered));
+
+        // Enabled: covered -> flagged -> questioned -> covered
+        game.set_question_marks(true);
+        assert_eq!(game.flag(0, 0), Some(true));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Flagged));
+        assert_eq!(game.flag(0, 0), Some(false));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Questioned));
+        assert_eq!(game.flagged(), 0);
+        assert_eq!(game.flag(0, 0), Some(false));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Covered));
+
+        // Question marks don't protect cells from being opened
+        game.cells = vec![GameCell::default(); (H * W) as usize];
+        game.flag(0, 0);
+        game.flag(0, 0);
+        assert!(game.open(0, 0));
+        assert_eq!(game.cell_state(0, 0), Some(CellState::Uncovered));
+    }
 }
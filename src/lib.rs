@@ -1,11 +1,18 @@
+#[cfg(feature = "debug_overlay")]
+use bevy::diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
 use bevy::input::mouse::*;
 use bevy::pbr::AmbientLight;
 use bevy::prelude::*;
+use bevy::window::WindowResized;
 use bevy_mod_picking::*;
+use game::{DifficultyLevel, Move};
 use rand::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
-mod game;
+pub mod game;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 enum GameState {
@@ -13,13 +20,8 @@ enum GameState {
     Playing,
     Over,
     Restart,
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
-enum DifficultyLevel {
-    Easy,
-    Medium,
-    Hard,
+    Tutorial,
+    Replay,
 }
 
 macro_rules! game {
@@ -38,6 +40,26 @@ struct Tile {
     x: u8,
     y: u8,
 }
+/// Scene displayed on top of a [Tile]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TileScene {
+    Trees,
+    Empty,
+    Flag,
+    Mine,
+}
+/// Sent whenever the board changes: a tile was opened, flagged, etc.
+struct BoardChanged;
+/// Set when the board needs to be re-rendered (see [render_board])
+#[derive(Debug, Default)]
+struct BoardDirty(bool);
+/// Material of a [Tile] which isn't hovered over
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TileMaterial {
+    Covered,
+    Uncovered,
+    Questioned,
+}
 /// Minesweeper Bevy plugin
 struct Minesweeper;
 /// Displays the number of adjacent mines.
@@ -53,17 +75,536 @@ struct TileMines;
 struct MinesLeft;
 /// Restarts the game with the current configuration
 struct RestartButton;
+/// Displays the share of the flags placed on mines and the covered tiles left
+struct FlagAccuracy;
 
 /// Displays the GameOver™ notification
 struct GameOver;
 /// Holds the game UI: [MinesLeft], [RestartButton] and [GameTimer]
 struct GameUI;
+/// Holds the [MinimapCell]s: a 2D overview of the board
+struct Minimap;
+/// Displays the state of the cell at the given coordinates on the [Minimap]
+struct MinimapCell {
+    x: u8,
+    y: u8,
+}
 /// Ambient light
 struct GameLight;
 /// Brings the user back to the Menu
 struct BackButton;
-/// Perspective camera
+/// Opens a random safe tile at the cost of [HINT_PENALTY]
+struct HintButton;
+/// Counts down to a new game once the game is over
+///
+/// Any mouse or keyboard input cancels the countdown.
+#[derive(Debug)]
+struct AutoRestart {
+    enabled: bool,
+    delay: Timer,
+}
+
+impl Default for AutoRestart {
+    fn default() -> Self {
+        AutoRestart::new(false, 0.)
+    }
+}
+
+impl AutoRestart {
+    /// The delay comes from the settings file, negative ones restart right away
+    fn new(enabled: bool, seconds: f32) -> Self {
+        AutoRestart {
+            enabled,
+            // `Timer` panics on negative durations
+            delay: Timer::from_seconds(seconds.max(0.), false),
+        }
+    }
+
+    /// Advances the countdown, returns `true` once it's time to restart
+    fn tick(&mut self, delta: std::time::Duration, interacted: bool) -> bool {
+        if interacted {
+            self.enabled = false;
+        }
+        self.enabled && self.delay.tick(delta).just_finished()
+    }
+}
+
+/// Outcomes of the games played since the start
+#[derive(Debug, Default)]
+struct SessionStats {
+    /// Parameters of the last finished game
+    params: Option<game::GameParams>,
+    /// Games lost in a row with these parameters
+    loss_streak: u32,
+}
+
+impl SessionStats {
+    fn record(&mut self, params: game::GameParams, lost: bool) {
+        if self.params != Some(params) {
+            self.params = Some(params);
+            self.loss_streak = 0;
+        }
+        self.loss_streak = if lost { self.loss_streak + 1 } else { 0 };
+    }
+
+    /// Games lost in a row on boards with the given parameters
+    fn loss_streak(&self, params: game::GameParams) -> u32 {
+        if self.params == Some(params) {
+            self.loss_streak
+        } else {
+            0
+        }
+    }
+}
+
+/// Best times of the games won since the start, per board parameters
+#[derive(Debug, Default)]
+struct HighScores {
+    best: HashMap<game::GameParams, u64>,
+}
+
+impl HighScores {
+    /// Stores the time of a win, returns whether it beat the best time so far
+    ///
+    /// The first win with the given parameters is always a record, a tie isn't.
+    fn record(&mut self, params: game::GameParams, seconds: u64) -> bool {
+        match self.best.get(&params) {
+            Some(&best) if best <= seconds => false,
+            _ => {
+                self.best.insert(params, seconds);
+                true
+            }
+        }
+    }
+}
+
+/// Time of the win just finished if it set a new [HighScores] record
+#[derive(Debug, Default)]
+struct NewRecord(Option<u64>);
+
+/// Help given on a new board after losing in a row (see [Settings::adaptive_assist])
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Assist {
+    None,
+    /// Open a random safe tile
+    SafeTile,
+    /// Open the largest region without adjacent mines
+    OpenRegion,
+}
+
+impl Assist {
+    /// Escalates the help with the loss streak
+    fn for_streak(loss_streak: u32) -> Self {
+        match loss_streak {
+            0 | 1 => Assist::None,
+            2 => Assist::SafeTile,
+            _ => Assist::OpenRegion,
+        }
+    }
+
+    /// Opens the tile with a [Move], so it's kept in the [game::Game::history]
+    /// and the [Replay] shows it
+    fn apply(self, game: &mut game::Game) {
+        match self {
+            Assist::None => {}
+            Assist::SafeTile => {
+                game.hint();
+            }
+            Assist::OpenRegion => {
+                let start = game
+                    .open_regions()
+                    .into_iter()
+                    .max_by_key(|region| region.len())
+                    .and_then(|region| {
+                        region
+                            .into_iter()
+                            .find(|&(x, y)| game.adjacent_mines(x, y) == Some(0))
+                    });
+                match start {
+                    Some((x, y)) => {
+                        game.apply_move(Move::Open(x, y));
+                    }
+                    // Every safe tile has a number on it
+                    None => Assist::SafeTile.apply(game),
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the board the current game started with, so it can be replayed
+/// once it's over
+///
+/// Inserted along with every new [game::Game]. The [Move]s to play back are
+/// taken from the [game::Game::history] when the replay starts.
+#[derive(Debug)]
+struct Replay {
+    initial: game::Game,
+    moves: Vec<Move>,
+}
+
+impl Replay {
+    fn new(initial: game::Game) -> Self {
+        Replay {
+            initial,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Returns the board after the first `steps` moves
+    fn board_at(&self, steps: usize) -> game::Game {
+        let mut game = self.initial.clone();
+        for m in self.moves.iter().take(steps) {
+            game.apply_move(*m);
+        }
+        game
+    }
+}
+
+/// Current step of the [GameState::Replay]
+struct ReplayPlayback {
+    step: usize,
+    timer: Timer,
+    /// Set once the replay reaches [GameState::Over], so the game that is
+    /// over again isn't counted twice
+    finished: bool,
+}
+
+impl Default for ReplayPlayback {
+    fn default() -> Self {
+        ReplayPlayback {
+            step: 0,
+            timer: Timer::from_seconds(REPLAY_STEP, true),
+            finished: false,
+        }
+    }
+}
+
+/// Seconds between the moves of a [Replay]
+const REPLAY_STEP: f32 = 0.5;
+/// Plays the [Replay] of the game that is over
+struct ReplayButton;
+/// The shared game booted with moves, see [play_shared_replay]
+struct SharedReplay;
+/// Starts the [GameState::Tutorial]
+struct TutorialButton;
+/// Displays the prompt of the current [TutorialStep]
+struct TutorialPrompt;
+/// Floats above the tile the current [TutorialStep] is about
+struct TutorialMarker;
+/// UI camera
+///
+/// Spawned once on startup and shared by all the states.
 struct UICamera;
+/// User settings
+///
+/// Loaded on startup and saved whenever they change: to the `localStorage`
+/// on WASM and to [SETTINGS_FILE] natively.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+struct Settings {
+    /// Ambient light brightness, adjustable from the menu
+    brightness: f32,
+    /// Left-clicking a satisfied number chords it (see [game::Game::chord])
+    auto_chord: bool,
+    /// Right-clicking a flag turns it into a question mark instead of removing it
+    question_marks: bool,
+    /// Place the mines symmetrically, selectable from the menu
+    symmetry: Symmetry,
+    /// Start a new game automatically once the game is over (see [AutoRestart])
+    auto_restart: bool,
+    /// Seconds to wait before restarting automatically
+    auto_restart_delay: f32,
+    /// Show how many mines are really left unflagged once the game is over
+    true_mines_left: bool,
+    /// Skip the decorative scenes and tile jitter, for slow devices
+    low_spec: bool,
+    /// Flag the remaining tiles once every safe tile is open
+    /// (see [game::Game::try_auto_complete])
+    ///
+    /// Only [game::WinCondition::FlagAllMines] games are won this way. Classic
+    /// ones are already won once every safe tile is open, so the mines only
+    /// turn into flags.
+    auto_complete: bool,
+    /// Right-clicking a satisfied number chords it (see [game::Game::chord])
+    right_click_chords: bool,
+    /// Show a [Minimap] of the board
+    minimap: bool,
+    /// What it takes to win, selectable from the menu
+    win_condition: game::WinCondition,
+    /// Put the first row at the front of the board instead of the back
+    flip_vertical: bool,
+    /// Open some tiles of a new board after losing several times in a row
+    /// (see [Assist])
+    adaptive_assist: bool,
+    /// Survive the first mine opened in every game (see [game::Game::set_mulligan])
+    mulligan: bool,
+    /// Show every mine as a flag once the game is won, instead of leaving
+    /// the unflagged ones hidden
+    win_reveals_flags: bool,
+    /// Keyboard controls, see [KeyBindings]
+    keys: KeyBindings,
+    /// Show how many flags are right while playing (see [FlagAccuracy]).
+    /// Gives away whether a flag is placed correctly
+    flag_accuracy: bool,
+    /// Lower and recolor uncovered tiles instead of spawning a scene for each
+    flat_uncovered: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            brightness: 1.0 / 5.0,
+            auto_chord: false,
+            question_marks: false,
+            symmetry: Symmetry::Off,
+            auto_restart: false,
+            auto_restart_delay: 5.,
+            true_mines_left: false,
+            low_spec: false,
+            auto_complete: false,
+            right_click_chords: false,
+            minimap: false,
+            win_condition: game::WinCondition::OpenAllSafe,
+            flip_vertical: false,
+            adaptive_assist: false,
+            mulligan: false,
+            win_reveals_flags: true,
+            keys: KeyBindings::default(),
+            flag_accuracy: false,
+            flat_uncovered: false,
+        }
+    }
+}
+
+impl Settings {
+    const MIN_BRIGHTNESS: f32 = 0.05;
+    const MAX_BRIGHTNESS: f32 = 1.0;
+    const BRIGHTNESS_STEP: f32 = 0.05;
+
+    /// Sets the brightness, keeping it within a range that keeps the board visible
+    fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = brightness.clamp(Self::MIN_BRIGHTNESS, Self::MAX_BRIGHTNESS);
+    }
+
+    /// Loads the saved settings, falling back to the defaults if there are
+    /// none or they can't be parsed
+    fn load() -> Self {
+        read_settings()
+            .and_then(|json| Self::parse(&json))
+            .unwrap_or_default()
+    }
+
+    /// Parses saved settings, bringing hand-edited values back into range
+    #[cfg(feature = "serde")]
+    fn parse(json: &str) -> Option<Self> {
+        let mut settings: Self = serde_json::from_str(json)
+            .map_err(|err| warn!("Failed to parse the settings: {}", err))
+            .ok()?;
+        settings.set_brightness(settings.brightness);
+        Some(settings)
+    }
+
+    /// Saved settings can't be read without the `serde` feature
+    #[cfg(not(feature = "serde"))]
+    fn parse(_json: &str) -> Option<Self> {
+        None
+    }
+
+    #[cfg(feature = "serde")]
+    fn save(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => write_settings(&json),
+            Err(err) => warn!("Failed to serialize the settings: {}", err),
+        }
+    }
+
+    /// The settings only last for the session without the `serde` feature
+    #[cfg(not(feature = "serde"))]
+    fn save(&self) {}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_FILE: &str = "settings.json";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_settings() -> Option<String> {
+    std::fs::read_to_string(SETTINGS_FILE).ok()
+}
+
+#[cfg(all(feature = "serde", not(target_arch = "wasm32")))]
+fn write_settings(json: &str) {
+    if let Err(err) = std::fs::write(SETTINGS_FILE, json) {
+        warn!("Failed to save the settings: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+const SETTINGS_KEY: &str = "minesweeper-settings";
+
+/// Reads a game and its moves shared with [write_shared_game], if any
+#[cfg(not(target_arch = "wasm32"))]
+fn read_shared_game() -> Option<(game::Game, Vec<Move>)> {
+    None
+}
+
+/// Logs the share code, there's no URL to put it in natively
+#[cfg(not(target_arch = "wasm32"))]
+fn write_shared_game(code: &str) {
+    info!("Share code: {}", code);
+}
+
+/// Reads a game and its moves shared through the URL fragment, if any
+#[cfg(target_arch = "wasm32")]
+fn read_shared_game() -> Option<(game::Game, Vec<Move>)> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    let code = hash.trim_start_matches('#');
+    if code.is_empty() {
+        return None;
+    }
+
+    game::decode_share(code)
+        .map_err(|err| warn!("Failed to load the shared game: {}", err))
+        .ok()
+}
+
+/// Puts the share code into the URL fragment, so the URL can be shared
+#[cfg(target_arch = "wasm32")]
+fn write_shared_game(code: &str) {
+    if web_sys::window()
+        .and_then(|window| window.location().set_hash(code).ok())
+        .is_none()
+    {
+        warn!("Failed to share the game");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_settings() -> Option<String> {
+    local_storage()?.get_item(SETTINGS_KEY).ok().flatten()
+}
+
+#[cfg(all(feature = "serde", target_arch = "wasm32"))]
+fn write_settings(json: &str) {
+    if local_storage()
+        .and_then(|storage| storage.set_item(SETTINGS_KEY, json).ok())
+        .is_none()
+    {
+        warn!("Failed to save the settings");
+    }
+}
+
+/// Something the player can do with the keyboard, see [KeyBindings]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    /// Move the camera to one of the board regions, numbered 1 to 9
+    /// like a numpad (see [quadrant_focus])
+    Focus(u8),
+    Restart,
+    Hint,
+    #[cfg(feature = "debug_overlay")]
+    DebugOverlay,
+}
+
+/// Keys bound to every [KeyAction], part of the [Settings]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+struct KeyBindings {
+    focus: [KeyCode; 9],
+    restart: KeyCode,
+    hint: KeyCode,
+    /// Only used with the `debug_overlay` feature
+    debug_overlay: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            focus: [
+                KeyCode::Key1,
+                KeyCode::Key2,
+                KeyCode::Key3,
+                KeyCode::Key4,
+                KeyCode::Key5,
+                KeyCode::Key6,
+                KeyCode::Key7,
+                KeyCode::Key8,
+                KeyCode::Key9,
+            ],
+            restart: KeyCode::R,
+            hint: KeyCode::H,
+            debug_overlay: KeyCode::F3,
+        }
+    }
+}
+
+impl KeyBindings {
+    fn key(&self, action: KeyAction) -> KeyCode {
+        match action {
+            KeyAction::Focus(digit) => self.focus[digit.clamp(1, 9) as usize - 1],
+            KeyAction::Restart => self.restart,
+            KeyAction::Hint => self.hint,
+            #[cfg(feature = "debug_overlay")]
+            KeyAction::DebugOverlay => self.debug_overlay,
+        }
+    }
+
+    fn just_pressed(&self, keys: &Input<KeyCode>, action: KeyAction) -> bool {
+        keys.just_pressed(self.key(action))
+    }
+}
+
+/// Symmetry of newly generated boards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Symmetry {
+    Off,
+    Mirror,
+    Rotational,
+}
+
+impl Symmetry {
+    /// The option selected by clicking the [SymmetryButton] next
+    fn next(self) -> Self {
+        match self {
+            Symmetry::Off => Symmetry::Mirror,
+            Symmetry::Mirror => Symmetry::Rotational,
+            Symmetry::Rotational => Symmetry::Off,
+        }
+    }
+
+    fn kind(self) -> Option<game::SymmetryKind> {
+        match self {
+            Symmetry::Off => None,
+            Symmetry::Mirror => Some(game::SymmetryKind::Horizontal),
+            Symmetry::Rotational => Some(game::SymmetryKind::Rotational),
+        }
+    }
+
+    fn label(self) -> String {
+        format!("Symmetry: {:?}", self)
+    }
+}
+
+/// Cycles through the [Symmetry] options when clicked
+struct SymmetryButton;
+/// Displays the selected [Symmetry]
+struct SymmetryLabel;
+
+/// Switches between the [game::WinCondition]s when clicked
+struct WinConditionButton;
+/// Displays the selected [game::WinCondition]
+struct WinConditionLabel;
+
+/// Changes the ambient light brightness by the given amount when clicked
+struct BrightnessButton(f32);
+/// Displays the current ambient light brightness
+struct BrightnessLabel;
 
 /// Holds the game menu: difficulty selection
 struct MenuUI;
@@ -73,6 +614,16 @@ struct GameTimer {
     ticks: u64,
 }
 
+impl GameTimer {
+    /// Adds the penalty to the elapsed time
+    fn penalize(&mut self, seconds: u64) {
+        self.ticks += seconds;
+    }
+}
+
+/// Seconds added to the elapsed time for every hint
+const HINT_PENALTY: u64 = 30;
+
 /// Used for orbiting the camera around the board (only around Y-axis)
 ///
 /// I took this code from https://bevy-cheatbook.github.io/cookbook/pan-orbit-camera.html
@@ -82,6 +633,8 @@ struct OrbitCamera {
     focus: Vec3,
     radius: f32,
     upside_down: bool,
+    /// The focus point the camera is moving towards (see [focus_camera])
+    target: Option<Vec3>,
 }
 
 impl Default for OrbitCamera {
@@ -90,6 +643,7 @@ impl Default for OrbitCamera {
             focus: Vec3::ZERO,
             radius: 5.0,
             upside_down: false,
+            target: None,
         }
     }
 }
@@ -99,8 +653,10 @@ struct GameMaterials {
     text_font: Handle<Font>,
     digit_font: Handle<Font>,
     notification_font: Handle<Font>,
-    tile_normal: Handle<StandardMaterial>,
+    tile_covered: Handle<StandardMaterial>,
+    tile_uncovered: Handle<StandardMaterial>,
     tile_hovered: Handle<StandardMaterial>,
+    tile_questioned: Handle<StandardMaterial>,
     tile: Handle<Mesh>,
     mine: Handle<Scene>,
     flag: Handle<Scene>,
@@ -116,7 +672,7 @@ impl FromWorld for GameMaterials {
             text_font,
             digit_font,
             notification_font,
-            tile_normal,
+            tile_covered,
             tile_hovered,
             tile,
             smile,
@@ -156,9 +712,21 @@ impl FromWorld for GameMaterials {
             })
             .expect("Couldn't get color materials");
 
+        let (tile_uncovered, tile_questioned) = world
+            .get_resource_mut::<Assets<StandardMaterial>>()
+            .map(|mut materials| {
+                (
+                    materials.add(Color::rgb(0.45, 0.4, 0.35).into()),
+                    materials.add(Color::rgb(0.9, 0.75, 0.3).into()),
+                )
+            })
+            .expect("Couldn't get standard materials");
+
         GameMaterials {
-            tile_normal,
+            tile_covered,
+            tile_uncovered,
             tile_hovered,
+            tile_questioned,
             flag,
             text_font,
             digit_font,
@@ -173,61 +741,173 @@ impl FromWorld for GameMaterials {
     }
 }
 
+impl GameMaterials {
+    fn scene(&self, scene: TileScene) -> Handle<Scene> {
+        match scene {
+            TileScene::Trees => self.trees.clone(),
+            TileScene::Empty => self.empty.clone(),
+            TileScene::Flag => self.flag.clone(),
+            TileScene::Mine => self.mine.clone(),
+        }
+    }
+
+    fn tile_material(&self, material: TileMaterial) -> Handle<StandardMaterial> {
+        match material {
+            TileMaterial::Covered => self.tile_covered.clone(),
+            TileMaterial::Uncovered => self.tile_uncovered.clone(),
+            TileMaterial::Questioned => self.tile_questioned.clone(),
+        }
+    }
+}
+
 impl Plugin for Minesweeper {
     fn build(&self, app: &mut AppBuilder) {
-        app.insert_resource(AmbientLight {
-            color: Color::WHITE,
-            brightness: 1.0 / 5.0f32,
-        })
-        .init_resource::<GameMaterials>()
-        .add_state(GameState::Menu)
-        .add_system_set(
-            SystemSet::on_enter(GameState::Menu)
-                .with_system(cleanup_board.system())
-                .with_system(cleanup_ui.system())
-                .with_system(cleanup_camera.system())
-                .with_system(setup_menu.system()),
-        )
-        .add_system_set(SystemSet::on_update(GameState::Menu).with_system(handle_menu.system()))
-        .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup_menu.system()))
-        .add_system_set(
-            SystemSet::on_enter(GameState::Playing)
-                .with_system(setup_scene.system())
-                .with_system(setup_board.system())
-                .with_system(setup_ui.system()),
-        )
-        .add_system_set(
-            SystemSet::on_update(GameState::Playing)
-                .with_system(handle_mouse_action.system())
-                .with_system(handle_highlight.system())
-                .with_system(update_mines.system())
-                .with_system(update_timer.system())
-                .with_system(orbit_camera.system())
-                .with_system(handle_restart.system())
-                .with_system(handle_back.system()),
-        )
-        .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over.system()))
-        .add_system_set(
-            SystemSet::on_update(GameState::Over)
-                .with_system(handle_restart.system())
-                .with_system(handle_back.system()),
-        )
-        .add_system_set(
-            SystemSet::on_enter(GameState::Restart)
-                .with_system(cleanup_board.system())
-                .with_system(cleanup_ui.system())
-                .with_system(cleanup_camera.system())
-                .with_system(restart.system()),
-        );
+        let settings = Settings::load();
+        // Shared games skip the menu, the ones with moves are replayed
+        let initial_state = match read_shared_game() {
+            Some((mut game, moves)) => {
+                game.set_question_marks(settings.question_marks);
+                game.set_win_condition(settings.win_condition);
+                game.set_mulligan(settings.mulligan);
+                let replay = Replay::new(game.clone());
+                if !moves.is_empty() {
+                    app.insert_resource(SharedReplay);
+                }
+                for m in moves {
+                    game.apply_move(m);
+                }
+                app.insert_resource(game.params())
+                    .insert_resource(replay)
+                    .insert_resource(game);
+                GameState::Playing
+            }
+            None => GameState::Menu,
+        };
+
+        app.insert_resource(settings.keys.clone())
+            .insert_resource(settings)
+            .init_resource::<GameMaterials>()
+            .add_event::<BoardChanged>()
+            .init_resource::<BoardDirty>()
+            .init_resource::<AutoRestart>()
+            .init_resource::<ReplayPlayback>()
+            .init_resource::<ClickQueue>()
+            .init_resource::<SessionStats>()
+            .init_resource::<HighScores>()
+            .init_resource::<NewRecord>()
+            .add_startup_system(setup_ui_camera.system())
+            .add_state(initial_state)
+            .add_system(update_light.system())
+            .add_system(save_settings.system())
+            .add_system(track_board_changes.system())
+            .add_system(queue_clicks.system())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(cleanup_board.system())
+                    .with_system(cleanup_ui.system())
+                    .with_system(cleanup_camera.system())
+                    .with_system(setup_menu.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(handle_menu.system())
+                    .with_system(handle_brightness.system())
+                    .with_system(handle_symmetry.system())
+                    .with_system(handle_win_condition.system())
+                    .with_system(handle_tutorial_button.system()),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup_menu.system()))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(share_game.system())
+                    .with_system(play_shared_replay.system())
+                    .with_system(setup_scene.system())
+                    .with_system(setup_board.system())
+                    .with_system(setup_ui.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(handle_mouse_action.system())
+                    .with_system(render_board.system())
+                    .with_system(handle_highlight.system())
+                    .with_system(update_mines.system())
+                    .with_system(update_flag_accuracy.system())
+                    .with_system(update_minimap.system())
+                    .with_system(update_timer.system())
+                    .with_system(orbit_camera.system())
+                    .with_system(focus_camera.system())
+                    .with_system(handle_restart.system())
+                    .with_system(handle_hint.system())
+                    .with_system(handle_back.system())
+                    .with_system(layout_ui.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Over)
+                    .with_system(record_high_score.system().label("high_score"))
+                    .with_system(game_over.system().after("high_score"))
+                    .with_system(share_game.system())
+                    .with_system(record_game.system())
+                    .with_system(start_auto_restart.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Over)
+                    .with_system(render_board.system())
+                    .with_system(update_mines.system())
+                    .with_system(handle_replay_button.system())
+                    .with_system(handle_auto_restart.system())
+                    .with_system(layout_ui.system())
+                    .with_system(handle_restart.system())
+                    .with_system(handle_back.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Over).with_system(reset_playback.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Replay).with_system(start_playback.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Replay)
+                    .with_system(step_playback.system())
+                    .with_system(render_board.system())
+                    .with_system(update_mines.system())
+                    .with_system(orbit_camera.system())
+                    .with_system(handle_restart.system())
+                    .with_system(handle_back.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Tutorial)
+                    .with_system(setup_scene.system())
+                    .with_system(setup_board.system())
+                    .with_system(setup_tutorial.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Tutorial)
+                    .with_system(handle_tutorial.system())
+                    .with_system(update_tutorial.system())
+                    .with_system(render_board.system())
+                    .with_system(handle_highlight.system())
+                    .with_system(orbit_camera.system())
+                    .with_system(handle_back.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Restart)
+                    .with_system(cleanup_board.system())
+                    .with_system(cleanup_ui.system())
+                    .with_system(cleanup_camera.system())
+                    .with_system(restart.system()),
+            );
     }
 }
 
-/// Sets up the game menu which allows for difficulty level selection
-fn setup_menu(mut commands: Commands, materials: Res<GameMaterials>) {
+/// Sets up the [UICamera]
+fn setup_ui_camera(mut commands: Commands) {
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(UICamera);
+}
 
+/// Sets up the game menu which allows for difficulty level selection
+fn setup_menu(mut commands: Commands, materials: Res<GameMaterials>, settings: Res<Settings>) {
     let text_style = TextStyle {
         font: materials.notification_font.clone(),
         font_size: 60.0,
@@ -286,564 +966,2101 @@ fn setup_menu(mut commands: Commands, materials: Res<GameMaterials>) {
                 })
                 .insert(DifficultyLevel::Hard);
         });
-}
-
-/// Handles user interactions with the menu
-///
-/// Starts the new game (changes to [GameState::Playing]) when a user selects
-/// a difficulty level.
-/// Handles hovering logic: the label text will increas by 20% if hovered
-/// over.
-fn handle_menu(
-    mut commands: Commands,
-    mut state: ResMut<State<GameState>>,
-    mut interaction_query: Query<
-        (&Interaction, &DifficultyLevel, &Children),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut text_query: Query<&mut Text>,
-) {
-    for (interaction, level, children) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Hovered => {
-                if let Ok(mut text) = text_query.get_mut(children[0]) {
-                    text.sections[0].style.font_size *= 1.2;
-                }
-            }
-            Interaction::Clicked => {
-                let game = match level {
-                    DifficultyLevel::Easy => game::Game::new(5, 5),
-                    DifficultyLevel::Medium => game::Game::new(10, 10),
-                    DifficultyLevel::Hard => game::Game::new(15, 15),
-                };
-
-                info!("\n{}", game);
-
-                commands.remove_resource::<game::Game>();
-                commands.insert_resource(game);
-
-                state
-                    .set(GameState::Playing)
-                    .expect("Failed to change the state");
-            }
-            Interaction::None => {
-                if let Ok(mut text) = text_query.get_mut(children[0]) {
-                    text.sections[0].style.font_size /= 1.2;
-                }
-            }
-        }
-    }
-}
-
-/// Cleans up the game menu
-fn cleanup_menu(mut commands: Commands, querry: Query<Entity, Or<(With<MenuUI>, With<UICamera>)>>) {
-    for entity in querry.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-/// Sets up a 3D scene
-///
-/// Settings up the scene in this case includes setting up a
-/// perspective camera and light.
-fn setup_scene(mut commands: Commands) {
-    let translation = Vec3::new(0., 15., 15.0);
-    let target = Vec3::ZERO;
-    let radius = translation.length();
 
-    commands
-        .spawn_bundle(PerspectiveCameraBundle {
-            transform: Transform::from_translation(translation).looking_at(target, Vec3::Y),
-            ..Default::default()
-        })
-        .insert(OrbitCamera {
-            radius,
-            focus: target,
-            ..Default::default()
-        })
-        .insert_bundle(PickingCameraBundle::default());
-    commands
-        .spawn_bundle(LightBundle {
-            transform: Transform::from_xyz(3.0, 5.0, 3.0),
-            ..Default::default()
-        })
-        .insert(GameLight);
-}
-
-/// Cleans up the 3D scene by despawning its components
-fn cleanup_camera(
-    mut commands: Commands,
-    query: Query<Entity, Or<(With<OrbitCamera>, With<GameLight>)>>,
-) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-/// Sets up the [GameUI] components: mines, timer labels and restart, back buttons
-///
-/// Components are configured to be a part of one bundle and their coordinates
-/// are relative to that bundle.
-fn setup_ui(
-    mut commands: Commands,
-    window: Res<WindowDescriptor>,
-    materials: Res<GameMaterials>,
-    game: Res<game::Game>,
-) {
-    commands
-        .spawn_bundle(UiCameraBundle::default())
-        .insert(UICamera);
-
-    let text_style = TextStyle {
-        font: materials.digit_font.clone(),
-        font_size: 40.,
-        color: Color::WHITE,
-    };
-
-    let h = window.height / 10.;
+    // Spawn ambient light controls
+    let text_style = TextStyle {
+        font_size: 30.0,
+        ..text_style
+    };
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Px(h)),
+                size: Size::new(Val::Percent(100.), Val::Percent(10.)),
                 position_type: PositionType::Absolute,
                 position: Rect {
-                    top: Val::Px(0.),
+                    bottom: Val::Px(0.),
                     left: Val::Px(0.),
                     ..Default::default()
                 },
-                // TODO: figure out how to center items properly
-                border: Rect {
-                    top: Val::Px(5.),
-                    left: Val::Px(15.),
-                    right: Val::Px(25.),
-                    ..Default::default()
-                },
-                justify_content: JustifyContent::SpaceBetween,
+                justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
                 ..Default::default()
             },
             material: materials.transparent.clone(),
             ..Default::default()
         })
-        .insert(GameUI)
+        .insert(MenuUI)
         .with_children(|parent| {
-            // Spawn timer label
+            parent
+                .spawn_bundle(ButtonBundle {
+                    material: materials.transparent.clone(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section("-", text_style.clone(), Default::default()),
+                        ..Default::default()
+                    });
+                })
+                .insert(BrightnessButton(-Settings::BRIGHTNESS_STEP));
             parent
                 .spawn_bundle(TextBundle {
-                    text: Text::with_section("Time:", text_style.clone(), Default::default()),
+                    text: Text::with_section(
+                        format!("Light: {:.0}%", settings.brightness * 100.),
+                        text_style.clone(),
+                        Default::default(),
+                    ),
                     style: Style {
-                        size: Size::new(Val::Percent(10.), Val::Percent(100.)),
+                        margin: Rect::all(Val::Px(15.)),
                         ..Default::default()
                     },
                     ..Default::default()
                 })
-                .insert(GameTimer {
-                    timer: Timer::from_seconds(1., true),
-                    ticks: 0,
-                });
-
+                .insert(BrightnessLabel);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    material: materials.transparent.clone(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section("+", text_style.clone(), Default::default()),
+                        ..Default::default()
+                    });
+                })
+                .insert(BrightnessButton(Settings::BRIGHTNESS_STEP));
             parent
                 .spawn_bundle(ButtonBundle {
+                    material: materials.transparent.clone(),
                     style: Style {
-                        size: Size::new(Val::Px(h), Val::Px(h)),
+                        margin: Rect {
+                            left: Val::Px(45.),
+                            ..Default::default()
+                        },
                         ..Default::default()
                     },
-                    material: materials.smiley.clone(),
                     ..Default::default()
                 })
-                .insert(RestartButton);
-
-            // Spawn mines label
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                settings.symmetry.label(),
+                                text_style.clone(),
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(SymmetryLabel);
+                })
+                .insert(SymmetryButton);
             parent
-                .spawn_bundle(TextBundle {
-                    text: Text::with_section(
-                        format!("Left: {}", game.mines()),
-                        text_style.clone(),
-                        Default::default(),
-                    ),
+                .spawn_bundle(ButtonBundle {
+                    material: materials.transparent.clone(),
                     style: Style {
-                        size: Size::new(Val::Percent(10.), Val::Percent(100.)),
+                        margin: Rect {
+                            left: Val::Px(45.),
+                            ..Default::default()
+                        },
                         ..Default::default()
                     },
                     ..Default::default()
                 })
-                .insert(MinesLeft);
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Tutorial",
+                            text_style.clone(),
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(TutorialButton);
         });
 
-    // Spawn 'Back' button
+    // Spawn game rules above the light controls
     commands
-        .spawn_bundle(ButtonBundle {
-            material: materials.transparent.clone(),
+        .spawn_bundle(NodeBundle {
             style: Style {
-                size: Size::new(Val::Px(h), Val::Px(h)),
+                size: Size::new(Val::Percent(100.), Val::Percent(10.)),
                 position_type: PositionType::Absolute,
                 position: Rect {
+                    bottom: Val::Percent(10.),
                     left: Val::Px(0.),
-                    bottom: Val::Px(0.),
                     ..Default::default()
                 },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
                 ..Default::default()
             },
+            material: materials.transparent.clone(),
             ..Default::default()
         })
+        .insert(MenuUI)
         .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::with_section("< Back", text_style, Default::default()),
-                ..Default::default()
-            });
-        })
-        .insert(BackButton);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    material: materials.transparent.clone(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                win_condition_label(settings.win_condition),
+                                text_style,
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(WinConditionLabel);
+                })
+                .insert(WinConditionButton);
+        });
 }
 
-/// Cleans up the [GameUI] components by despawning them
-fn cleanup_ui(
-    mut commands: Commands,
-    ui_query: Query<
-        Entity,
-        Or<(
-            With<GameUI>,
-            With<GameOver>,
-            With<GameTimer>,
-            With<BackButton>,
-            With<UICamera>,
-        )>,
-    >,
-) {
-    for entity in ui_query.iter() {
-        commands.entity(entity).despawn_recursive();
+/// Action a mouse click results in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TileAction {
+    Open,
+    Chord,
+    Flag,
+}
+
+/// Decides what a left click on a tile should do
+///
+/// Opening an uncovered tile has no effect, so with [Settings::auto_chord] enabled
+/// a click on a satisfied number chords it instead.
+fn left_click_action(state: game::CellState, satisfied: bool, auto_chord: bool) -> TileAction {
+    match state {
+        game::CellState::Uncovered if satisfied && auto_chord => TileAction::Chord,
+        _ => TileAction::Open,
     }
 }
 
-/// Creates a graphical representation of the [game::Game]
-fn setup_board(mut commands: Commands, materials: Res<GameMaterials>, game: Res<game::Game>) {
-    for y in 0..game.height() {
-        for x in 0..game.width() {
-            let height = rand::thread_rng().gen_range(-0.1..0.1);
-            // In order to place the scene at some 3D location it should
-            // be spawned as child of some other bundle. In this case I'm using
-            // a PbrBundle with the same tile mesh and material as the scene
-            // so I can select/highligh the tile, without decompositing the scene.
-            // See https://github.com/aevyrie/bevy_mod_picking/blob/master/examples/
-            commands
-                .spawn_bundle(PbrBundle {
-                    transform: Transform::from_translation(Vec3::new(
-                        x as f32 - game.width() as f32 / 2.,
-                        height - 0.2,
-                        y as f32 - game.height() as f32 / 2.,
-                    )),
-                    material: materials.tile_normal.clone(),
-                    mesh: materials.tile.clone(),
-                    ..Default::default()
-                })
-                .insert(Tile { x, y })
-                .insert_bundle(PickableBundle::default())
-                .with_children(|parent| {
-                    parent.spawn_scene(materials.trees.clone());
-                });
-        }
+/// Decides what a right click on a tile should do
+///
+/// Flagging an uncovered tile has no effect, so with [Settings::right_click_chords]
+/// enabled a click on a satisfied number chords it instead.
+fn right_click_action(
+    state: game::CellState,
+    satisfied: bool,
+    right_click_chords: bool,
+) -> TileAction {
+    match state {
+        game::CellState::Uncovered if satisfied && right_click_chords => TileAction::Chord,
+        _ => TileAction::Flag,
     }
 }
 
-/// Destroys the graphical representation of the board
-fn cleanup_board(mut commands: Commands, tile_query: Query<Entity, With<Tile>>) {
-    for entity in tile_query.iter() {
-        commands.entity(entity).despawn_recursive();
+/// Decides what a middle click or pressing the left and right buttons
+/// together on a tile should do
+///
+/// Only a satisfied number chords, anywhere else it does nothing at all.
+fn chord_click_action(state: game::CellState, satisfied: bool) -> Option<TileAction> {
+    match state {
+        game::CellState::Uncovered if satisfied => Some(TileAction::Chord),
+        _ => None,
     }
 }
 
-/// Handles mouse actions.
+/// Handles user interactions with the menu
 ///
-/// Despite of the title, this is the main system for our game.
-/// It picks the tile, user clicked on last and either opens or
-/// flags it depending on the mouse button clicked.
-/// Those actions may result in the state transition from
-/// [GameState::Playing] to [GameState::Over] if the game was
-/// won or lost.
-fn handle_mouse_action(
+/// Starts the new game (changes to [GameState::Playing]) when a user selects
+/// a difficulty level.
+/// Handles hovering logic: the label text will increas by 20% if hovered
+/// over.
+fn handle_menu(
     mut commands: Commands,
     mut state: ResMut<State<GameState>>,
-    button: Res<Input<MouseButton>>,
-    materials: Res<GameMaterials>,
-    mut game: ResMut<game::Game>,
-    picking_camera_query: Query<&PickingCamera>,
-    mut tile_query: Query<(&Tile, Entity, &Children), With<Tile>>,
+    settings: Res<Settings>,
+    stats: Res<SessionStats>,
+    mut interaction_query: Query<
+        (&Interaction, &DifficultyLevel, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut text_query: Query<&mut Text>,
 ) {
-    // First get a tile a user hovered over.
-    // See https://github.com/aevyrie/bevy_mod_picking
-    let (tile, entity, children) = if let Some(query) = picking_camera_query
-        .iter()
-        .last()
-        .and_then(|picking_camera| picking_camera.intersect_top())
-        .and_then(|(entity, _intersection)| tile_query.get_mut(entity).ok())
-    {
-        query
-    } else {
-        return;
-    };
+    for (interaction, level, children) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Hovered => {
+                if let Ok(mut text) = text_query.get_mut(children[0]) {
+                    text.sections[0].style.font_size *= 1.2;
+                }
+            }
+            Interaction::Clicked => {
+                let mut game = generate_game(level.params(), settings.symmetry);
+                game.set_question_marks(settings.question_marks);
+                game.set_win_condition(settings.win_condition);
+                game.set_mulligan(settings.mulligan);
+                // The assist is part of the replay
+                commands.insert_resource(Replay::new(game.clone()));
+                if settings.adaptive_assist {
+                    Assist::for_streak(stats.loss_streak(level.params())).apply(&mut game);
+                }
 
-    trace!("{}", game!(game, dump, tile.x, tile.y));
+                info!("\n{}", game);
 
-    // If a user clicked on the cell - either open or flag it
-    if button.just_pressed(MouseButton::Left) {
-        game.open(tile.x, tile.y);
-    } else if button.just_pressed(MouseButton::Right) {
-        match game.flag(tile.x, tile.y) {
-            Some(true) => {
-                for entity in children.iter() {
-                    commands.entity(*entity).despawn_recursive();
-                }
+                commands.insert_resource(game.params());
+                commands.remove_resource::<game::Game>();
+                commands.insert_resource(game);
+                commands.insert_resource(*level);
 
-                commands.entity(entity).with_children(|parent| {
-                    parent.spawn_scene(materials.empty.clone());
-                    parent.spawn_scene(materials.flag.clone());
-                });
+                state
+                    .set(GameState::Playing)
+                    .expect("Failed to change the state");
             }
-            Some(false) => {
-                for entity in children.iter() {
-                    commands.entity(*entity).despawn_recursive();
+            Interaction::None => {
+                if let Ok(mut text) = text_query.get_mut(children[0]) {
+                    text.sections[0].style.font_size /= 1.2;
                 }
-
-                commands.entity(entity).with_children(|parent| {
-                    parent.spawn_scene(materials.trees.clone());
-                });
             }
-            _ => unreachable!(),
-        };
+        }
     }
+}
 
-    // Reflect on the game state:
-    // 1. If the game continues it's possible that a user clicked open and more cells were uncovered.
-    // 2. If the game is won - flagged cells should be marked as mined
-    // 3. If the game is lost - all mined cells should be uncovered.
-    let (entities, scene) = match game.state() {
-        game::GameState::Continue => {
-            let entities = tile_query
-                .iter()
-                .filter(|(tile, _entity, _children)| {
-                    game!(game, cell_state, tile.x, tile.y) == game::CellState::Uncovered
-                })
-                .map(|(_tile, entity, children)| (entity, children))
-                .collect::<Vec<(Entity, &Children)>>();
-
-            (entities, materials.empty.clone())
+/// Handles the ambient light controls in the menu
+fn handle_brightness(
+    mut settings: ResMut<Settings>,
+    interaction_query: Query<(&Interaction, &BrightnessButton), Changed<Interaction>>,
+    mut text_query: Query<&mut Text, With<BrightnessLabel>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            let brightness = settings.brightness + button.0;
+            settings.set_brightness(brightness);
         }
-        game::GameState::Won => {
-            let entities = tile_query
-                .iter()
-                .filter(|(tile, _entity, _children)| {
-                    game!(game, cell_state, tile.x, tile.y) == game::CellState::Flagged
-                })
-                .map(|(_tile, entity, children)| (entity, children))
-                .collect::<Vec<(Entity, &Children)>>();
+    }
 
-            state
-                .set(GameState::Over)
-                .expect("Failed to change the game state");
-            (entities, materials.flag.clone())
+    if settings.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = format!("Light: {:.0}%", settings.brightness * 100.);
         }
-        game::GameState::Lost => {
-            let entities = tile_query
-                .iter()
-                .filter(|(tile, _entity, _children)| game!(game, has_mine, tile.x, tile.y))
-                .map(|(_tile, entity, children)| (entity, children))
-                .collect::<Vec<(Entity, &Children)>>();
+    }
+}
 
-            state
-                .set(GameState::Over)
-                .expect("Failed to change the game state");
-            (entities, materials.mine.clone())
+/// Cycles [Settings::symmetry] when the [SymmetryButton] is clicked
+fn handle_symmetry(
+    mut settings: ResMut<Settings>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SymmetryButton>)>,
+    mut text_query: Query<&mut Text, With<SymmetryLabel>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            settings.symmetry = settings.symmetry.next();
+            for mut text in text_query.iter_mut() {
+                text.sections[0].value = settings.symmetry.label();
+            }
         }
-    };
+    }
+}
 
-    for (entity, children) in entities {
-        for entity in children.iter() {
-            commands.entity(*entity).despawn_recursive();
+/// Text of the [WinConditionButton] for the given [game::WinCondition]
+fn win_condition_label(condition: game::WinCondition) -> String {
+    match condition {
+        game::WinCondition::OpenAllSafe => "Win: open all safe tiles",
+        game::WinCondition::FlagAllMines => "Win: flag all mines",
+    }
+    .to_string()
+}
+
+/// Switches [Settings::win_condition] when the [WinConditionButton] is clicked
+fn handle_win_condition(
+    mut settings: ResMut<Settings>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<WinConditionButton>)>,
+    mut text_query: Query<&mut Text, With<WinConditionLabel>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            settings.win_condition = match settings.win_condition {
+                game::WinCondition::OpenAllSafe => game::WinCondition::FlagAllMines,
+                game::WinCondition::FlagAllMines => game::WinCondition::OpenAllSafe,
+            };
+            for mut text in text_query.iter_mut() {
+                text.sections[0].value = win_condition_label(settings.win_condition);
+            }
         }
-        commands.entity(entity).with_children(|parent| {
-            parent.spawn_scene(scene.clone());
+    }
+}
+
+/// Generates a new board with the given parameters and [Symmetry]
+fn generate_game(params: game::GameParams, symmetry: Symmetry) -> game::Game {
+    match symmetry.kind() {
+        Some(kind) => game::Game::generate(
+            params.width,
+            params.height,
+            params.mines,
+            &game::SymmetryGenerator { kind },
+        ),
+        None => game::Game::from_params(params),
+    }
+}
+
+/// Applies [Settings::brightness] to the ambient light whenever the settings change
+fn update_light(mut commands: Commands, settings: Res<Settings>) {
+    if settings.is_changed() {
+        commands.insert_resource(AmbientLight {
+            color: Color::WHITE,
+            brightness: settings.brightness,
         });
     }
 }
 
-/// Handle tile highlighting.
-fn handle_highlight(
+/// Saves the [Settings] whenever they change
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
+
+/// Cleans up the game menu
+fn cleanup_menu(mut commands: Commands, querry: Query<Entity, With<MenuUI>>) {
+    for entity in querry.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Scale of the scene lighting relative to the smallest board
+fn light_scale(w: u8, h: u8) -> f32 {
+    (w.max(h) as f32 / 5.).max(1.)
+}
+
+/// Position of the scene light, moved away from the board center
+/// as the board gets bigger so its corners aren't left in the dark
+fn light_position(w: u8, h: u8) -> Vec3 {
+    Vec3::new(3.0, 5.0, 3.0) * light_scale(w, h)
+}
+
+/// Sets up a 3D scene
+///
+/// Settings up the scene in this case includes setting up a
+/// perspective camera and light.
+fn setup_scene(mut commands: Commands, game: Res<game::Game>) {
+    let translation = Vec3::new(0., 15., 15.0);
+    let target = Vec3::ZERO;
+    let radius = translation.length();
+
+    commands
+        .spawn_bundle(PerspectiveCameraBundle {
+            transform: Transform::from_translation(translation).looking_at(target, Vec3::Y),
+            ..Default::default()
+        })
+        .insert(OrbitCamera {
+            radius,
+            focus: target,
+            ..Default::default()
+        })
+        .insert_bundle(PickingCameraBundle::default());
+    // The light is its own entity, so orbiting the camera doesn't affect it
+    let scale = light_scale(game.width(), game.height());
+    let light = Light::default();
+    commands
+        .spawn_bundle(LightBundle {
+            transform: Transform::from_translation(light_position(game.width(), game.height())),
+            light: Light {
+                // Keep the same brightness on the board from further away
+                intensity: light.intensity * scale * scale,
+                range: light.range * scale,
+                ..light
+            },
+            ..Default::default()
+        })
+        .insert(GameLight);
+}
+
+/// Cleans up the 3D scene by despawning its components
+fn cleanup_camera(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<OrbitCamera>, With<GameLight>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Sets up the [GameUI] components: mines, timer labels and restart, back buttons
+///
+/// Every component is positioned absolutely according to the [UiLayout]
+/// of the current window, see [layout_ui] for how they follow resizes.
+fn setup_ui(
     mut commands: Commands,
     windows: Res<Windows>,
     materials: Res<GameMaterials>,
+    settings: Res<Settings>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
     game: Res<game::Game>,
-    mut interaction_query: Query<
-        (&Tile, &Interaction, &mut Handle<StandardMaterial>),
-        (Or<(Changed<Interaction>, Changed<Selection>)>, With<Tile>),
-    >,
-    text_query: Query<Entity, With<TileMines>>,
 ) {
     let window = windows
         .get_primary()
         .expect("Couldn't get the primary window");
+    let layout = UiLayout::new(window.width(), window.height(), settings.flag_accuracy);
 
-    for (tile, interaction, mut material) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Hovered => {
-                // When hovered over - show the number of adjacent mines
-                let cursor_position = window.cursor_position().unwrap_or(Vec2::ZERO);
-                let mines = game!(game, adjacent_mines, tile.x, tile.y);
-                let state = game!(game, cell_state, tile.x, tile.y);
+    let text_style = TextStyle {
+        font: materials.digit_font.clone(),
+        font_size: layout.font_size,
+        color: Color::WHITE,
+    };
 
-                if state == game::CellState::Uncovered {
-                    commands
-                        .spawn_bundle(TextBundle {
-                            text: Text::with_section(
-                                format!("Mines: {}", mines),
-                                TextStyle {
-                                    font: materials.text_font.clone(),
-                                    font_size: 10.0,
-                                    color: Color::WHITE,
-                                },
-                                Default::default(),
-                            ),
+    // Spawn timer label
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section("Time: 0s", text_style.clone(), Default::default()),
+            style: layout.style(UiSlot::Timer),
+            ..Default::default()
+        })
+        .insert(UiSlot::Timer)
+        .insert(GameUI)
+        .insert(GameTimer {
+            timer: Timer::from_seconds(1., true),
+            ticks: 0,
+        });
+
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: layout.style(UiSlot::Restart),
+            material: materials.smiley.clone(),
+            ..Default::default()
+        })
+        .insert(UiSlot::Restart)
+        .insert(GameUI)
+        .insert(RestartButton);
+
+    // Spawn mines label
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                format!("Left: {}", game.mines()),
+                text_style.clone(),
+                Default::default(),
+            ),
+            style: layout.style(UiSlot::MinesLeft),
+            ..Default::default()
+        })
+        .insert(UiSlot::MinesLeft)
+        .insert(GameUI)
+        .insert(MinesLeft);
+
+    if settings.flag_accuracy {
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    flag_accuracy_label(&game),
+                    text_style.clone(),
+                    Default::default(),
+                ),
+                style: layout.style(UiSlot::Accuracy),
+                ..Default::default()
+            })
+            .insert(UiSlot::Accuracy)
+            .insert(GameUI)
+            .insert(FlagAccuracy);
+    }
+
+    // Spawn 'Back' button
+    commands
+        .spawn_bundle(ButtonBundle {
+            material: materials.transparent.clone(),
+            style: layout.style(UiSlot::Back),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section("< Back", text_style.clone(), Default::default()),
+                ..Default::default()
+            });
+        })
+        .insert(UiSlot::Back)
+        .insert(BackButton);
+
+    // Spawn 'Hint' button
+    commands
+        .spawn_bundle(ButtonBundle {
+            material: materials.transparent.clone(),
+            style: layout.style(UiSlot::Hint),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    format!("Hint (+{}s)", HINT_PENALTY),
+                    text_style,
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        })
+        .insert(UiSlot::Hint)
+        .insert(HintButton);
+
+    if settings.minimap {
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        right: Val::Px(UI_GAP),
+                        bottom: Val::Px(layout.minimap_bottom()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                material: materials.transparent.clone(),
+                ..Default::default()
+            })
+            .insert(Minimap)
+            .with_children(|parent| {
+                for y in 0..game.height() {
+                    parent
+                        .spawn_bundle(NodeBundle {
                             style: Style {
-                                position_type: PositionType::Absolute,
-                                position: Rect {
-                                    bottom: Val::Px(cursor_position.y + 5.),
-                                    left: Val::Px(cursor_position.x),
-                                    ..Default::default()
-                                },
+                                flex_direction: FlexDirection::Row,
                                 ..Default::default()
                             },
+                            material: materials.transparent.clone(),
                             ..Default::default()
                         })
-                        .insert(TileMines);
-                }
-                // Change the tile material
-                *material = materials.tile_hovered.clone();
-            }
-            _ => {
-                // Stop showing the number of adjacent mines
-                for entity in text_query.iter() {
-                    commands.entity(entity).despawn_recursive();
+                        .with_children(|parent| {
+                            for x in 0..game.width() {
+                                let color = minimap_color(game!(game, cell_state, x, y), false);
+                                parent
+                                    .spawn_bundle(NodeBundle {
+                                        style: Style {
+                                            size: Size::new(
+                                                Val::Px(MINIMAP_CELL),
+                                                Val::Px(MINIMAP_CELL),
+                                            ),
+                                            margin: Rect::all(Val::Px(0.5)),
+                                            ..Default::default()
+                                        },
+                                        material: color_materials.add(color.into()),
+                                        ..Default::default()
+                                    })
+                                    .insert(MinimapCell { x, y });
+                            }
+                        });
                 }
-                // Restore the tile material to its previous state
-                match game!(game, cell_state, tile.x, tile.y) {
-                    game::CellState::Covered => *material = materials.tile_normal.clone(),
-                    game::CellState::Uncovered => *material = materials.tile_normal.clone(),
-                    _ => {}
-                };
-            }
-        }
+            });
     }
 }
 
-/// Updates the timer [GameTimer] label.
-fn update_timer(
-    time: Res<Time>,
-    game: Res<game::Game>,
-    mut text_query: Query<(&mut Text, &mut GameTimer), With<GameTimer>>,
-) {
-    if let Some((mut text, mut game_timer)) = text_query.iter_mut().last() {
-        if game.state() != game::GameState::Continue {
-            game_timer.timer.reset();
+/// Smallest height of the top bar and the bottom buttons, unless the
+/// window is too small to fit even that
+const MIN_BAR_HEIGHT: f32 = 24.;
+/// Space kept between neighbouring UI elements
+const UI_GAP: f32 = 5.;
+
+/// Screen-space rectangle of a UI element, relative to the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct UiRect {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+/// Marks an element of the game UI, so it can be laid out again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UiSlot {
+    Timer,
+    Restart,
+    MinesLeft,
+    /// Below the top bar, only with [Settings::flag_accuracy]
+    Accuracy,
+    Back,
+    Hint,
+}
+
+/// Positions and sizes of the game UI for a given window size
+///
+/// The labels sit at the top next to the restart button with an optional
+/// row below them, the 'Back' and 'Hint' buttons at the bottom. Everything
+/// shrinks together when the window gets too small, so no two elements ever
+/// overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct UiLayout {
+    width: f32,
+    height: f32,
+    /// Height of the top bar and the bottom buttons
+    bar: f32,
+    font_size: f32,
+}
+
+impl UiLayout {
+    /// The [UiSlot::Accuracy] row only takes space if `accuracy_row` is set
+    fn new(width: f32, height: f32, accuracy_row: bool) -> Self {
+        let rows = if accuracy_row { 3. } else { 2. };
+        let bar = (height / 10.)
+            .max(MIN_BAR_HEIGHT)
+            // All the rows have to fit vertically...
+            .min((height - UI_GAP) / rows)
+            // ...and both bottom buttons (2 + 3 bars wide) horizontally
+            .min((width - UI_GAP) / 5.)
+            .max(0.);
+
+        UiLayout {
+            width,
+            height,
+            bar,
+            // 40 at the default window size
+            font_size: bar * 5. / 9.,
         }
+    }
 
-        if game_timer.timer.tick(time.delta()).just_finished() {
-            text.sections[0].value = format!("Time: {}s", game_timer.ticks);
-            game_timer.ticks += 1;
+    /// Width of the timer and the mines labels, each
+    fn label_width(&self) -> f32 {
+        ((self.width - self.bar) / 2. - 2. * UI_GAP).max(0.)
+    }
+
+    fn rect(&self, slot: UiSlot) -> UiRect {
+        let bar = self.bar;
+        let bottom = self.height - bar;
+        let (left, top, width) = match slot {
+            UiSlot::Timer => (UI_GAP, 0., self.label_width()),
+            UiSlot::Restart => ((self.width - bar) / 2., 0., bar),
+            UiSlot::MinesLeft => (
+                self.width - UI_GAP - self.label_width(),
+                0.,
+                self.label_width(),
+            ),
+            UiSlot::Accuracy => (UI_GAP, bar, (self.width - 2. * UI_GAP).max(0.)),
+            UiSlot::Back => (0., bottom, 2. * bar),
+            UiSlot::Hint => (self.width - 3. * bar, bottom, 3. * bar),
+        };
+
+        UiRect {
+            left,
+            top,
+            width,
+            height: bar,
         }
     }
-}
 
-/// Updates the [TileMines] label.
-fn update_mines(game: Res<game::Game>, mut text_query: Query<&mut Text, With<MinesLeft>>) {
-    if let Some(mut text) = text_query.iter_mut().last() {
-        text.sections[0].value = format!("Left: {}", game.mines() - game.flagged());
+    fn style(&self, slot: UiSlot) -> Style {
+        let rect = self.rect(slot);
+        Style {
+            size: Size::new(Val::Px(rect.width), Val::Px(rect.height)),
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(rect.left),
+                top: Val::Px(rect.top),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Distance of the [Minimap] from the bottom of the window
+    fn minimap_bottom(&self) -> f32 {
+        self.bar + UI_GAP
     }
 }
 
-/// Checks if the [RestartButton] was pressed and schedules a restart
-fn handle_restart(
-    mut state: ResMut<State<GameState>>,
-    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<RestartButton>)>,
+/// Lays the game UI out again when the window gets resized
+fn layout_ui(
+    mut events: EventReader<WindowResized>,
+    settings: Res<Settings>,
+    mut slot_query: Query<(Entity, &UiSlot, &mut Style, Option<&Children>)>,
+    mut minimap_query: Query<&mut Style, (With<Minimap>, Without<UiSlot>)>,
+    mut text_query: Query<&mut Text>,
 ) {
-    for interaction in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => {
-                state
-                    .set(GameState::Restart)
-                    .expect("Failed to reset the game state");
+    let layout = match events.iter().last() {
+        Some(event) => UiLayout::new(event.width, event.height, settings.flag_accuracy),
+        None => return,
+    };
 
-                break;
+    for (entity, slot, mut style, children) in slot_query.iter_mut() {
+        *style = layout.style(*slot);
+
+        // Labels hold their text themselves, buttons - in their children
+        let children = children.map_or(&[][..], |children| &children[..]);
+        for entity in std::iter::once(&entity).chain(children) {
+            if let Ok(mut text) = text_query.get_mut(*entity) {
+                for section in text.sections.iter_mut() {
+                    section.style.font_size = layout.font_size;
+                }
             }
-            _ => {}
         }
     }
+
+    for mut style in minimap_query.iter_mut() {
+        style.position.bottom = Val::Px(layout.minimap_bottom());
+    }
 }
 
-/// Checks if the [BackButton] was pressed and brings the user to
-/// the difficulty selection menu.
-fn handle_back(
-    mut state: ResMut<State<GameState>>,
-    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+/// Size of a [MinimapCell] in pixels
+const MINIMAP_CELL: f32 = 6.;
+
+/// Decides the color of a [MinimapCell]
+fn minimap_color(cell: game::CellState, hovered: bool) -> Color {
+    if hovered {
+        return Color::WHITE;
+    }
+
+    match cell {
+        game::CellState::Covered => Color::rgb(0.2, 0.5, 0.2),
+        game::CellState::Uncovered => Color::rgb(0.45, 0.4, 0.35),
+        game::CellState::Flagged => Color::RED,
+        game::CellState::Questioned => Color::rgb(0.9, 0.75, 0.3),
+    }
+}
+
+/// Recolors the [MinimapCell]s from the board and the hovered tile
+fn update_minimap(
+    game: Res<game::Game>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    tile_query: Query<(&Tile, &Interaction)>,
+    cell_query: Query<(&MinimapCell, &Handle<ColorMaterial>)>,
 ) {
-    for interaction in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => {
-                state
-                    .set(GameState::Menu)
-                    .expect("Failed to reset the game state");
-                break;
+    let hovered = tile_query
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Hovered)
+        .map(|(tile, _)| (tile.x, tile.y));
+
+    for (cell, handle) in cell_query.iter() {
+        let color = minimap_color(
+            game!(game, cell_state, cell.x, cell.y),
+            hovered == Some((cell.x, cell.y)),
+        );
+        // Only touch the materials that actually change
+        if color_materials.get(handle).map(|material| material.color) != Some(color) {
+            if let Some(material) = color_materials.get_mut(handle) {
+                material.color = color;
             }
-            _ => {}
         }
     }
 }
 
-/// Restarts the game
-fn restart(mut commands: Commands, mut state: ResMut<State<GameState>>, game: Res<game::Game>) {
-    // TODO: make this conditional
-    trace!("Restarting the game");
+/// Cleans up the [GameUI] components by despawning them
+fn cleanup_ui(
+    mut commands: Commands,
+    ui_query: Query<
+        Entity,
+        Or<(
+            With<GameUI>,
+            With<GameOver>,
+            With<GameTimer>,
+            With<BackButton>,
+            With<HintButton>,
+            With<TutorialMarker>,
+            With<Minimap>,
+        )>,
+    >,
+) {
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
 
-    commands.remove_resource::<game::Game>();
-    commands.insert_resource(game::Game::new(game.height(), game.width()));
+/// Scale of the tiles, so they don't get too small on large boards
+///
+/// Grows with the square root of the board size, so the board footprint
+/// stays reasonable.
+fn tile_scale(w: u8, h: u8) -> f32 {
+    (w.max(h) as f32 / 10.).sqrt().max(1.)
+}
+
+/// Position of the center of a tile on the ground
+///
+/// See [row_z] for `flip`.
+fn tile_position(w: u8, h: u8, x: u8, y: u8, flip: bool) -> Vec3 {
+    Vec3::new(x as f32 - w as f32 / 2., 0., row_z(h, y, flip)) * tile_scale(w, h)
+}
+
+/// Unscaled world Z of a board row
+///
+/// Row 0 is at the back of the board, unless `flip` is set
+/// (see [Settings::flip_vertical]). Game coordinates stay the same either way.
+fn row_z(h: u8, y: u8, flip: bool) -> f32 {
+    let row = if flip { h - 1 - y } else { y };
+    row as f32 - h as f32 / 2.
+}
+
+/// Creates a graphical representation of the [game::Game]
+fn setup_board(
+    mut commands: Commands,
+    materials: Res<GameMaterials>,
+    settings: Res<Settings>,
+    game: Res<game::Game>,
+    mut board_changed: EventWriter<BoardChanged>,
+) {
+    board_changed.send(BoardChanged);
+
+    let scale = tile_scale(game.width(), game.height());
+    for y in 0..game.height() {
+        for x in 0..game.width() {
+            let height = if settings.low_spec {
+                0.
+            } else {
+                rand::thread_rng().gen_range(-0.1..0.1)
+            };
+            // In order to place the scene at some 3D location it should
+            // be spawned as child of some other bundle. In this case I'm using
+            // a PbrBundle with the same tile mesh and material as the scene
+            // so I can select/highligh the tile, without decompositing the scene.
+            // See https://github.com/aevyrie/bevy_mod_picking/blob/master/examples/
+            commands
+                .spawn_bundle(PbrBundle {
+                    transform: Transform {
+                        translation: tile_position(
+                            game.width(),
+                            game.height(),
+                            x,
+                            y,
+                            settings.flip_vertical,
+                        ) + Vec3::new(0., height - 0.2, 0.),
+                        // Child scenes and picking follow the tile scale
+                        scale: Vec3::splat(scale),
+                        ..Default::default()
+                    },
+                    material: materials.tile_covered.clone(),
+                    mesh: materials.tile.clone(),
+                    ..Default::default()
+                })
+                .insert(Tile { x, y })
+                .insert(TileScene::Trees)
+                .insert_bundle(PickableBundle::default())
+                .with_children(|parent| {
+                    for layer in scene_layers(TileScene::Trees, settings.low_spec) {
+                        parent.spawn_scene(materials.scene(*layer));
+                    }
+                });
+        }
+    }
+}
+
+/// Destroys the graphical representation of the board
+fn cleanup_board(mut commands: Commands, tile_query: Query<Entity, With<Tile>>) {
+    for entity in tile_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Mouse clicks that haven't been handled yet
+///
+/// On a long frame a press and a release may both happen before
+/// the next update, so [Input::just_pressed] would miss the click.
+/// Every click is queued instead and [handle_mouse_action] drains the queue.
+#[derive(Debug, Default)]
+struct ClickQueue {
+    clicks: std::collections::VecDeque<Click>,
+    /// Left and right buttons held down at the moment
+    held: Vec<MouseButton>,
+    /// Whether the held buttons were pressed together, so releasing them
+    /// doesn't click on its own
+    chording: bool,
+    /// Distance the mouse moved since the held buttons were pressed
+    dragged: f32,
+}
+
+/// Pixels the mouse may move between a press and its release for it to
+/// still click: dragging orbits the camera instead (see [orbit_camera])
+const DRAG_THRESHOLD: f32 = 5.;
+
+/// A press queued in the [ClickQueue]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Click {
+    Button(MouseButton),
+    /// Left and right buttons held down together, the classic way to chord
+    Both,
+}
+
+impl ClickQueue {
+    fn push(&mut self, button: MouseButton) {
+        self.clicks.push_back(Click::Button(button));
+    }
+
+    /// Holds a button down, queues [Click::Both] if the other one is already held
+    ///
+    /// A single button only clicks once released, like in the classic game,
+    /// so pressing the other one in the meantime chords instead of opening or
+    /// flagging the tile.
+    fn press(&mut self, button: MouseButton) {
+        let other = match button {
+            MouseButton::Left => MouseButton::Right,
+            _ => MouseButton::Left,
+        };
+        if self.held.is_empty() {
+            self.dragged = 0.;
+        }
+        if !self.held.contains(&button) {
+            self.held.push(button);
+        }
+
+        if self.held.contains(&other) {
+            self.chording = true;
+            self.clicks.push_back(Click::Both);
+        }
+    }
+
+    /// Queues the click of a held button, unless it was part of a [Click::Both]
+    /// or the mouse was dragged meanwhile
+    fn release(&mut self, button: MouseButton) {
+        if self.held.contains(&button) && !self.chording && self.dragged <= DRAG_THRESHOLD {
+            self.push(button);
+        }
+        self.forget(button);
+    }
+
+    /// Keeps track of the mouse moving while a button is held
+    fn drag(&mut self, delta: Vec2) {
+        if !self.held.is_empty() {
+            self.dragged += delta.length();
+        }
+    }
+
+    /// Lets go of a button without clicking
+    fn forget(&mut self, button: MouseButton) {
+        self.held.retain(|held| *held != button);
+        if self.held.is_empty() {
+            self.chording = false;
+        }
+    }
+
+    fn drain(&mut self) -> std::collections::vec_deque::Drain<'_, Click> {
+        self.clicks.drain(..)
+    }
+}
+
+/// Feeds the [ClickQueue] with the left, right and middle button clicks
+/// and with the mouse motion that tells them apart from drags
+///
+/// Runs in every state, so its reader never falls behind, but only
+/// queues clicks made while playing: the click that started the game
+/// shouldn't open a tile, nor should a button released once it's over.
+fn queue_clicks(
+    state: Res<State<GameState>>,
+    mut events: EventReader<MouseButtonInput>,
+    mut motion: EventReader<MouseMotion>,
+    mut queue: ResMut<ClickQueue>,
+) {
+    for event in motion.iter() {
+        queue.drag(event.delta);
+    }
+
+    let playing = *state.current() == GameState::Playing;
+    for event in events.iter() {
+        match (event.button, event.state.is_pressed()) {
+            (MouseButton::Left | MouseButton::Right, false) if playing => {
+                queue.release(event.button)
+            }
+            (MouseButton::Left | MouseButton::Right, false) => queue.forget(event.button),
+            (MouseButton::Left | MouseButton::Right, true) if playing => queue.press(event.button),
+            (MouseButton::Middle, true) if playing => queue.push(event.button),
+            _ => {}
+        }
+    }
+}
+
+/// Applies queued clicks to the tile at the given coordinates
+///
+/// Every click results in at most a single [Move]. Returns the moves
+/// that changed the board, stopping as soon as the game is over.
+fn apply_clicks(
+    game: &mut game::Game,
+    settings: &Settings,
+    clicks: impl Iterator<Item = Click>,
+    x: u8,
+    y: u8,
+) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for click in clicks {
+        if game.is_over() {
+            break;
+        }
+
+        let cell = game!(game, cell_state, x, y);
+        let satisfied = game.is_satisfied(x, y);
+        let action = match click {
+            Click::Button(MouseButton::Left) => {
+                left_click_action(cell, satisfied, settings.auto_chord)
+            }
+            Click::Button(MouseButton::Right) => {
+                right_click_action(cell, satisfied, settings.right_click_chords)
+            }
+            Click::Both | Click::Button(MouseButton::Middle) => {
+                match chord_click_action(cell, satisfied) {
+                    Some(action) => action,
+                    None => continue,
+                }
+            }
+            Click::Button(_) => continue,
+        };
+        let m = match action {
+            TileAction::Open => Move::Open(x, y),
+            TileAction::Chord => Move::Chord(x, y),
+            TileAction::Flag => Move::Flag(x, y),
+        };
+
+        if !game.apply_move(m).changed() {
+            continue;
+        }
+        moves.push(m);
+        if settings.auto_complete && !matches!(m, Move::Flag(..)) {
+            game.try_auto_complete();
+        }
+    }
+
+    reveal_flags_on_win(game, settings);
+
+    moves
+}
+
+/// Flags every mine once the game is won, if [Settings::win_reveals_flags]
+///
+/// [Settings::auto_complete] does the same to a classic game. Not a [Move] of
+/// its own, so the [Replay] does it as well to end on the same board.
+fn reveal_flags_on_win(game: &mut game::Game, settings: &Settings) {
+    if game.is_won() && (settings.win_reveals_flags || settings.auto_complete) {
+        game.flag_all_mines();
+    }
+}
+
+/// Handles mouse actions.
+///
+/// Despite of the title, this is the main system for our game.
+/// It picks the tile, user clicked on last and either opens or
+/// flags it depending on the mouse button clicked.
+/// Those actions may result in the state transition from
+/// [GameState::Playing] to [GameState::Over] if the game was
+/// won or lost.
+#[allow(clippy::too_many_arguments)]
+fn handle_mouse_action(
+    mut state: ResMut<State<GameState>>,
+    mut board_changed: EventWriter<BoardChanged>,
+    mut queue: ResMut<ClickQueue>,
+    settings: Res<Settings>,
+    mut game: ResMut<game::Game>,
+    picking_camera_query: Query<&PickingCamera>,
+    tile_query: Query<&Tile>,
+) {
+    // First get a tile a user hovered over.
+    // See https://github.com/aevyrie/bevy_mod_picking
+    let tile = if let Some(tile) = picking_camera_query
+        .iter()
+        .last()
+        .and_then(|picking_camera| picking_camera.intersect_top())
+        .and_then(|(entity, _intersection)| tile_query.get(entity).ok())
+    {
+        tile
+    } else {
+        // Clicks outside of the board don't do anything
+        queue.drain();
+        return;
+    };
+
+    trace!("{}", game!(game, dump, tile.x, tile.y));
+
+    // If a user clicked on the cell - either open or flag it.
+    // The board is re-rendered by [render_board].
+    let moves = apply_clicks(&mut game, &settings, queue.drain(), tile.x, tile.y);
+    if moves.is_empty() {
+        return;
+    }
+
+    board_changed.send(BoardChanged);
+    if game.is_over() {
+        state
+            .set(GameState::Over)
+            .expect("Failed to change the game state");
+    }
+}
+
+/// Decides which scene a tile should display
+///
+/// 1. While the game continues uncovered cells are empty and flagged cells show a flag.
+/// 2. If the game is lost - all mined cells are uncovered.
+/// 3. If the game is won - the board stays as is.
+fn tile_scene(cell: game::CellState, mine: bool, state: game::GameState) -> TileScene {
+    match (cell, mine, state) {
+        (_, true, game::GameState::Lost) => TileScene::Mine,
+        (game::CellState::Uncovered, ..) => TileScene::Empty,
+        (game::CellState::Flagged, ..) => TileScene::Flag,
+        _ => TileScene::Trees,
+    }
+}
+
+/// Components [render_board] needs to render a tile
+type TileRenderQuery<'a> = (
+    &'a Tile,
+    Entity,
+    &'a Children,
+    &'a mut TileScene,
+    &'a mut Handle<StandardMaterial>,
+    &'a Interaction,
+    &'a mut Transform,
+);
+
+/// Scene assets a [TileScene] is made of, each named after the scene it's used for
+///
+/// In the low spec mode the decorative trees and grass are skipped: tile
+/// materials tell covered and uncovered tiles apart anyway.
+fn scene_layers(scene: TileScene, low_spec: bool) -> &'static [TileScene] {
+    match (scene, low_spec) {
+        (TileScene::Trees, false) => &[TileScene::Trees],
+        (TileScene::Empty, false) => &[TileScene::Empty],
+        (TileScene::Flag, false) => &[TileScene::Empty, TileScene::Flag],
+        (TileScene::Trees, true) | (TileScene::Empty, true) => &[],
+        (TileScene::Flag, true) => &[TileScene::Flag],
+        (TileScene::Mine, _) => &[TileScene::Mine],
+    }
+}
+
+/// Scene assets [render_board] spawns on a tile
+///
+/// With [Settings::flat_uncovered] uncovered tiles don't get any,
+/// [tile_offset] lowers them instead.
+fn tile_layers(scene: TileScene, settings: &Settings) -> &'static [TileScene] {
+    if settings.flat_uncovered && scene == TileScene::Empty {
+        return &[];
+    }
+    scene_layers(scene, settings.low_spec)
+}
+
+/// How far below a covered tile a flat uncovered one sits
+const FLAT_DEPTH: f32 = 0.1;
+
+/// Height of a tile displaying the scene relative to a covered tile
+fn tile_offset(scene: TileScene, settings: &Settings) -> f32 {
+    if settings.flat_uncovered && scene == TileScene::Empty {
+        -FLAT_DEPTH
+    } else {
+        0.
+    }
+}
+
+/// Decides which material a tile should have when it's not hovered over
+///
+/// Flagged tiles look covered, the flag on top of them tells them apart.
+fn tile_material(cell: game::CellState) -> TileMaterial {
+    match cell {
+        game::CellState::Covered | game::CellState::Flagged => TileMaterial::Covered,
+        game::CellState::Uncovered => TileMaterial::Uncovered,
+        game::CellState::Questioned => TileMaterial::Questioned,
+    }
+}
+
+/// Renders the board as a function of the [game::Game] state
+///
+/// Every tile keeps track of the [TileScene] it displays, so only
+/// the tiles whose cells changed get their scenes respawned. Does nothing
+/// unless the board is [BoardDirty].
+fn render_board(
+    mut commands: Commands,
+    materials: Res<GameMaterials>,
+    settings: Res<Settings>,
+    game: Res<game::Game>,
+    mut dirty: ResMut<BoardDirty>,
+    mut tile_query: Query<TileRenderQuery>,
+) {
+    if !dirty.0 {
+        return;
+    }
+    dirty.0 = false;
+
+    for (tile, entity, children, mut scene, mut material, interaction, mut transform) in
+        tile_query.iter_mut()
+    {
+        let cell = game!(game, cell_state, tile.x, tile.y);
+        // Hovered tiles get their material back by [handle_highlight]
+        if *interaction != Interaction::Hovered {
+            *material = materials.tile_material(tile_material(cell));
+        }
+
+        let wanted = tile_scene(cell, game!(game, has_mine, tile.x, tile.y), game.state());
+        if *scene == wanted {
+            continue;
+        }
+
+        for entity in children.iter() {
+            commands.entity(*entity).despawn_recursive();
+        }
+        commands.entity(entity).with_children(|parent| {
+            for layer in tile_layers(wanted, &settings) {
+                parent.spawn_scene(materials.scene(*layer));
+            }
+        });
+        transform.translation.y += tile_offset(wanted, &settings) - tile_offset(*scene, &settings);
+        *scene = wanted;
+    }
+}
+
+/// Marks the board [BoardDirty] if any [BoardChanged] events were sent
+fn track_board_changes(mut events: EventReader<BoardChanged>, mut dirty: ResMut<BoardDirty>) {
+    if events.iter().count() > 0 {
+        dirty.0 = true;
+    }
+}
+
+/// Handle tile highlighting.
+fn handle_highlight(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    materials: Res<GameMaterials>,
+    game: Res<game::Game>,
+    mut interaction_query: Query<
+        (&Tile, &Interaction, &mut Handle<StandardMaterial>),
+        (Or<(Changed<Interaction>, Changed<Selection>)>, With<Tile>),
+    >,
+    text_query: Query<Entity, With<TileMines>>,
+) {
+    // Only tiles whose interaction changed are queried, nothing to do otherwise
+    if interaction_query.iter_mut().next().is_none() {
+        return;
+    }
+
+    let window = windows
+        .get_primary()
+        .expect("Couldn't get the primary window");
+
+    let mut labels_cleared = false;
+    let mut label_spawned = false;
+
+    for (tile, interaction, mut material) in interaction_query.iter_mut() {
+        // Something changed: the previously shown label is stale. Clicking
+        // a hovered tile doesn't change its interaction, so without this
+        // labels would pile up on top of each other.
+        if !labels_cleared {
+            for entity in text_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            labels_cleared = true;
+        }
+
+        match *interaction {
+            Interaction::Hovered => {
+                // When hovered over - show the coordinates and the number
+                // of adjacent mines
+                let cursor_position = window.cursor_position().unwrap_or(Vec2::ZERO);
+                // Only a single tile can be hovered over at a time
+                if !label_spawned {
+                    label_spawned = true;
+                    commands
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                tile_tooltip(&game, tile.x, tile.y),
+                                TextStyle {
+                                    font: materials.text_font.clone(),
+                                    font_size: 10.0,
+                                    color: Color::WHITE,
+                                },
+                                Default::default(),
+                            ),
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: Rect {
+                                    bottom: Val::Px(cursor_position.y + 5.),
+                                    left: Val::Px(cursor_position.x),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(TileMines);
+                }
+                // Change the tile material
+                *material = materials.tile_hovered.clone();
+            }
+            _ => {
+                // Restore the tile material to its previous state
+                *material =
+                    materials.tile_material(tile_material(game!(game, cell_state, tile.x, tile.y)));
+            }
+        }
+    }
+}
+
+/// Text of the [TileMines] tooltip
+///
+/// Only uncovered tiles have numbers on them, covered ones just show
+/// their coordinates so nothing is spoiled.
+fn tile_tooltip(game: &game::Game, x: u8, y: u8) -> String {
+    let mines = game.visible_number(x, y);
+    let remaining = game.remaining_mines_around(x, y);
+    match (mines, remaining) {
+        (Some(1), Some(remaining)) => format!("({},{}): 1 mine, to flag: {}", x, y, remaining),
+        (Some(mines), Some(remaining)) => {
+            format!("({},{}): {} mines, to flag: {}", x, y, mines, remaining)
+        }
+        _ => format!("({},{})", x, y),
+    }
+}
+
+/// Checks whether the [GameTimer] should be running
+///
+/// The timer only runs from the first move until the game is over,
+/// so players can look around before starting
+fn timer_running(started: bool, state: game::GameState) -> bool {
+    started && state == game::GameState::Continue
+}
+
+/// Updates the timer [GameTimer] label.
+fn update_timer(
+    time: Res<Time>,
+    game: Res<game::Game>,
+    mut text_query: Query<(&mut Text, &mut GameTimer), With<GameTimer>>,
+) {
+    if let Some((mut text, mut game_timer)) = text_query.iter_mut().last() {
+        if !timer_running(game.has_started(), game.state()) {
+            game_timer.timer.reset();
+            return;
+        }
+
+        if game_timer.timer.tick(time.delta()).just_finished() {
+            text.sections[0].value = format!("Time: {}s", game_timer.ticks);
+            game_timer.ticks += 1;
+        }
+    }
+}
+
+/// Updates the [TileMines] label.
+fn update_mines(
+    game: Res<game::Game>,
+    settings: Res<Settings>,
+    mut text_query: Query<&mut Text, With<MinesLeft>>,
+) {
+    if !game.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    if let Some(mut text) = text_query.iter_mut().last() {
+        text.sections[0].value = mines_left_label(&game, settings.true_mines_left);
+    }
+}
+
+/// Updates the [FlagAccuracy] label
+fn update_flag_accuracy(
+    game: Res<game::Game>,
+    mut text_query: Query<&mut Text, With<FlagAccuracy>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = flag_accuracy_label(&game);
+    }
+}
+
+/// Percentage of the flags placed on mines, `None` without any flags
+fn flag_accuracy(correct: usize, flagged: usize) -> Option<usize> {
+    if flagged == 0 {
+        return None;
+    }
+    Some(correct * 100 / flagged)
+}
+
+/// Text of the [FlagAccuracy] label
+fn flag_accuracy_label(game: &game::Game) -> String {
+    let accuracy = match flag_accuracy(game.correctly_flagged(), game.flagged()) {
+        Some(accuracy) => format!("{}%", accuracy),
+        None => "-".to_string(),
+    };
+    format!(
+        "Flags right: {} Covered: {}",
+        accuracy,
+        game.covered_count()
+    )
+}
+
+/// Text of the [MinesLeft] label
+///
+/// The real number of unflagged mines is only revealed once the game is over.
+fn mines_left_label(game: &game::Game, true_mines_left: bool) -> String {
+    // Flagging more tiles than there are mines takes the count below zero
+    let left = game.mines() as isize - game.flagged() as isize;
+    if true_mines_left && game.is_over() {
+        format!("Left: {} (really {})", left, game.unflagged_mines())
+    } else {
+        format!("Left: {}", left)
+    }
+}
+
+/// Lets a board be copied out of the log (see [game::Game::from_json])
+#[cfg(feature = "serde")]
+fn dump_game(game: &game::Game) -> String {
+    game.to_json()
+}
+
+/// Only the board can be logged without the `serde` feature
+#[cfg(not(feature = "serde"))]
+fn dump_game(game: &game::Game) -> String {
+    format!("\n{}", game)
+}
+
+/// Checks if the [RestartButton] was pressed and schedules a restart
+fn handle_restart(
+    mut state: ResMut<State<GameState>>,
+    game: Res<game::Game>,
+    bindings: Res<KeyBindings>,
+    keys: Res<Input<KeyCode>>,
+    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<RestartButton>)>,
+) {
+    let mut restart = bindings.just_pressed(&keys, KeyAction::Restart);
+    for interaction in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                restart = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if restart {
+        trace!("Restarting {}", dump_game(&game));
+        state
+            .set(GameState::Restart)
+            .expect("Failed to reset the game state");
+    }
+}
+
+/// Checks if the [HintButton] was pressed and opens a safe tile
+///
+/// Every hint adds [HINT_PENALTY] to the [GameTimer].
+#[allow(clippy::too_many_arguments)]
+fn handle_hint(
+    mut state: ResMut<State<GameState>>,
+    mut board_changed: EventWriter<BoardChanged>,
+    settings: Res<Settings>,
+    bindings: Res<KeyBindings>,
+    keys: Res<Input<KeyCode>>,
+    mut game: ResMut<game::Game>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<HintButton>)>,
+    mut timer_query: Query<&mut GameTimer>,
+) {
+    let clicked = interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked);
+    if !clicked && !bindings.just_pressed(&keys, KeyAction::Hint) {
+        return;
+    }
+
+    if game.hint().is_some() {
+        board_changed.send(BoardChanged);
+        for mut timer in timer_query.iter_mut() {
+            timer.penalize(HINT_PENALTY);
+        }
+
+        reveal_flags_on_win(&mut game, &settings);
+        if game.is_over() {
+            state
+                .set(GameState::Over)
+                .expect("Failed to change the game state");
+        }
+    }
+}
+
+/// Checks if the [BackButton] was pressed and brings the user to
+/// the difficulty selection menu.
+fn handle_back(
+    mut state: ResMut<State<GameState>>,
+    game: Res<game::Game>,
+    mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    for interaction in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                trace!("Leaving {}", dump_game(&game));
+                state
+                    .set(GameState::Menu)
+                    .expect("Failed to reset the game state");
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// What the player is asked to do in a [TutorialStep]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TutorialAction {
+    Open,
+    Flag,
+    Chord,
+}
+
+#[derive(Debug)]
+struct TutorialStep {
+    prompt: &'static str,
+    action: TutorialAction,
+    x: u8,
+    y: u8,
+}
+
+/// Steps of the tutorial, played on the [tutorial_game] board
+const TUTORIAL_STEPS: [TutorialStep; 3] = [
+    TutorialStep {
+        prompt: "Left-click the marked tile to open it",
+        action: TutorialAction::Open,
+        x: 0,
+        y: 4,
+    },
+    TutorialStep {
+        prompt: "Numbers count the adjacent mines. Right-click the marked tile to flag the mine",
+        action: TutorialAction::Flag,
+        x: 3,
+        y: 0,
+    },
+    TutorialStep {
+        prompt: "This number has all its mines flagged. Left-click it to open its neighbors",
+        action: TutorialAction::Chord,
+        x: 4,
+        y: 1,
+    },
+];
+
+/// Index of the current [TutorialStep]
+#[derive(Debug, Default, PartialEq, Eq)]
+struct TutorialProgress(usize);
+
+impl TutorialProgress {
+    fn step(&self) -> Option<&'static TutorialStep> {
+        TUTORIAL_STEPS.get(self.0)
+    }
+
+    fn prompt(&self) -> &'static str {
+        self.step()
+            .map(|step| step.prompt)
+            .unwrap_or("Well done! You are ready to play")
+    }
+
+    /// Moves on to the next step if the player did what the current one asks
+    fn advance(&mut self, action: TutorialAction, x: u8, y: u8) -> bool {
+        match self.step() {
+            Some(step) if step.action == action && step.x == x && step.y == y => {
+                self.0 += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Creates the tutorial board: a single mine, which takes every [TutorialStep] to clear
+fn tutorial_game() -> game::Game {
+    let mut cells = vec![game::GameCell::default(); 5 * 5];
+    cells[3].mine = true;
+    game::Game::from_cells(5, 5, cells).expect("Invalid tutorial board")
+}
+
+/// Position of the [TutorialMarker] above the given tile
+fn tutorial_marker_position(game: &game::Game, x: u8, y: u8, flip: bool) -> Vec3 {
+    tile_position(game.width(), game.height(), x, y, flip) + Vec3::Y
+}
+
+/// Starts the tutorial on its scripted board when the [TutorialButton] is clicked
+fn handle_tutorial_button(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<TutorialButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            commands.remove_resource::<game::Game>();
+            commands.insert_resource(tutorial_game());
+            commands.insert_resource(TutorialProgress::default());
+
+            state
+                .set(GameState::Tutorial)
+                .expect("Failed to change the state");
+            break;
+        }
+    }
+}
+
+/// Spawns the tutorial prompt, the 'Back' button and the marker on the next tile
+fn setup_tutorial(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<GameMaterials>,
+    settings: Res<Settings>,
+    game: Res<game::Game>,
+    progress: Res<TutorialProgress>,
+) {
+    let text_style = TextStyle {
+        font: materials.notification_font.clone(),
+        font_size: 30.,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(10.)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.),
+                    left: Val::Px(0.),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.transparent.clone(),
+            ..Default::default()
+        })
+        .insert(GameUI)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        progress.prompt(),
+                        text_style.clone(),
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(TutorialPrompt);
+        });
+
+    commands
+        .spawn_bundle(ButtonBundle {
+            material: materials.transparent.clone(),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.),
+                    bottom: Val::Px(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section("< Back", text_style, Default::default()),
+                ..Default::default()
+            });
+        })
+        .insert(BackButton);
+
+    if let Some(step) = progress.step() {
+        commands
+            .spawn_bundle(PbrBundle {
+                transform: Transform::from_translation(tutorial_marker_position(
+                    &game,
+                    step.x,
+                    step.y,
+                    settings.flip_vertical,
+                )),
+                material: materials.tile_questioned.clone(),
+                mesh: meshes.add(Mesh::from(shape::Icosphere {
+                    radius: 0.15,
+                    subdivisions: 3,
+                })),
+                ..Default::default()
+            })
+            .insert(TutorialMarker);
+    }
+}
+
+/// Lets the player do only what the current [TutorialStep] asks
+fn handle_tutorial(
+    mut board_changed: EventWriter<BoardChanged>,
+    button: Res<Input<MouseButton>>,
+    mut game: ResMut<game::Game>,
+    mut progress: ResMut<TutorialProgress>,
+    picking_camera_query: Query<&PickingCamera>,
+    tile_query: Query<&Tile>,
+) {
+    let tile = if let Some(tile) = picking_camera_query
+        .iter()
+        .last()
+        .and_then(|picking_camera| picking_camera.intersect_top())
+        .and_then(|(entity, _intersection)| tile_query.get(entity).ok())
+    {
+        tile
+    } else {
+        return;
+    };
+
+    let action = if button.just_pressed(MouseButton::Left) {
+        match game!(game, cell_state, tile.x, tile.y) {
+            game::CellState::Uncovered => TutorialAction::Chord,
+            _ => TutorialAction::Open,
+        }
+    } else if button.just_pressed(MouseButton::Right) {
+        TutorialAction::Flag
+    } else {
+        return;
+    };
+
+    if !progress.advance(action, tile.x, tile.y) {
+        return;
+    }
+
+    match action {
+        TutorialAction::Open => game.open(tile.x, tile.y),
+        TutorialAction::Flag => game.flag(tile.x, tile.y).is_some(),
+        TutorialAction::Chord => game.chord(tile.x, tile.y),
+    };
+    board_changed.send(BoardChanged);
+}
+
+/// Points the [TutorialPrompt] and [TutorialMarker] to the current [TutorialStep]
+fn update_tutorial(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    game: Res<game::Game>,
+    progress: Res<TutorialProgress>,
+    mut prompt_query: Query<&mut Text, With<TutorialPrompt>>,
+    mut marker_query: Query<(Entity, &mut Transform), With<TutorialMarker>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for mut text in prompt_query.iter_mut() {
+        text.sections[0].value = progress.prompt().to_string();
+    }
+    for (entity, mut transform) in marker_query.iter_mut() {
+        match progress.step() {
+            Some(step) => {
+                transform.translation =
+                    tutorial_marker_position(&game, step.x, step.y, settings.flip_vertical);
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+/// Starts the [AutoRestart] countdown once the game is over
+///
+/// Not again after its [Replay]: the countdown was already given its chance.
+fn start_auto_restart(
+    settings: Res<Settings>,
+    playback: Res<ReplayPlayback>,
+    mut auto_restart: ResMut<AutoRestart>,
+) {
+    if playback.finished {
+        return;
+    }
+    *auto_restart = AutoRestart::new(settings.auto_restart, settings.auto_restart_delay);
+}
+
+/// Restarts the game when the [AutoRestart] countdown runs out
+fn handle_auto_restart(
+    time: Res<Time>,
+    mouse: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut auto_restart: ResMut<AutoRestart>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let interacted =
+        mouse.get_just_pressed().next().is_some() || keys.get_just_pressed().next().is_some();
+    if auto_restart.tick(time.delta(), interacted) {
+        state
+            .set(GameState::Restart)
+            .expect("Failed to reset the game state");
+    }
+}
+
+/// Restarts the game
+///
+/// The new board is generated with the same [game::GameParams] as the
+/// one selected in the menu: same dimensions and the number of mines.
+fn restart(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    settings: Res<Settings>,
+    stats: Res<SessionStats>,
+    params: Res<game::GameParams>,
+) {
+    // TODO: make this conditional
+    trace!("Restarting the game");
+
+    let mut game = generate_game(*params, settings.symmetry);
+    game.set_question_marks(settings.question_marks);
+    game.set_win_condition(settings.win_condition);
+    game.set_mulligan(settings.mulligan);
+    // The assist is part of the replay
+    commands.insert_resource(Replay::new(game.clone()));
+    if settings.adaptive_assist {
+        Assist::for_streak(stats.loss_streak(*params)).apply(&mut game);
+    }
+
+    commands.remove_resource::<game::Game>();
+    commands.insert_resource(game);
+
+    state
+        .set(GameState::Playing)
+        .expect("Failed to reset the game state");
+}
+
+/// Keeps track of the [SessionStats] once a game is over
+///
+/// Only the games actually played count, not their [Replay].
+fn record_game(
+    game: Res<game::Game>,
+    playback: Res<ReplayPlayback>,
+    mut stats: ResMut<SessionStats>,
+) {
+    if playback.finished {
+        return;
+    }
+    stats.record(game.params(), game.is_lost());
+}
+
+/// Checks whether a won game beat the [HighScores] and keeps the result in [NewRecord]
+fn record_high_score(
+    game: Res<game::Game>,
+    playback: Res<ReplayPlayback>,
+    mut scores: ResMut<HighScores>,
+    mut new_record: ResMut<NewRecord>,
+    timer_query: Query<&GameTimer>,
+) {
+    // A finished replay keeps the result of the game it replayed
+    if playback.finished {
+        return;
+    }
+    let seconds = timer_query.iter().last().map_or(0, |timer| timer.ticks);
+    new_record.0 = if game.is_won() && scores.record(game.params(), seconds) {
+        Some(seconds)
+    } else {
+        None
+    };
+}
+
+/// Text shown when the game is over, see [game_over]
+fn game_over_message(won: bool, new_record: Option<u64>) -> String {
+    match (won, new_record) {
+        (true, Some(seconds)) => format!("New record: {}s!", seconds),
+        (true, None) => "Game over. You won!".to_string(),
+        (false, _) => "Game over. You lost!".to_string(),
+    }
+}
+
+/// Displays the score when the game is over.
+///
+/// Celebrates a [NewRecord]. If the game was lost, offers to try a new board
+/// of the same difficulty.
+fn game_over(
+    mut commands: Commands,
+    game: Res<game::Game>,
+    new_record: Res<NewRecord>,
+    game_materials: Res<GameMaterials>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                margin: Rect::all(Val::Auto),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: game_materials.transparent.clone(),
+            ..Default::default()
+        })
+        .insert(GameOver)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    game_over_message(game.is_won(), new_record.0),
+                    TextStyle {
+                        font: game_materials.notification_font.clone(),
+                        font_size: 80.0,
+                        color: if new_record.0.is_some() {
+                            Color::GOLD
+                        } else {
+                            Color::BLACK
+                        },
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            if game.is_lost() {
+                // Restarting keeps the difficulty (see [restart]),
+                // so this is just a more prominent restart button
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        material: game_materials.transparent.clone(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "Try similar",
+                                TextStyle {
+                                    font: game_materials.notification_font.clone(),
+                                    font_size: 40.0,
+                                    color: Color::BLACK,
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+                    })
+                    .insert(RestartButton);
+            }
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    material: game_materials.transparent.clone(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Replay",
+                            TextStyle {
+                                font: game_materials.notification_font.clone(),
+                                font_size: 40.0,
+                                color: Color::BLACK,
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(ReplayButton);
+        });
+}
+
+/// Makes the game shareable (see [game::encode_share])
+///
+/// Runs when the game starts and again once it's over, so the shared code
+/// includes the moves and opens as a [Replay].
+fn share_game(game: Res<game::Game>, replay: Res<Replay>) {
+    write_shared_game(&game::encode_share(&replay.initial, game.history()));
+}
+
+/// Switches a shared game with moves straight to the [GameState::Replay]
+/// once its board is set up
+fn play_shared_replay(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    shared: Option<Res<SharedReplay>>,
+) {
+    if shared.is_some() {
+        commands.remove_resource::<SharedReplay>();
+        state
+            .set(GameState::Replay)
+            .expect("Failed to change the game state");
+    }
+}
+
+/// Plays the [Replay] of the finished game when the [ReplayButton] is clicked
+fn handle_replay_button(
+    mut state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ReplayButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            state
+                .set(GameState::Replay)
+                .expect("Failed to change the game state");
+            break;
+        }
+    }
+}
+
+/// Resets the board to the beginning of the [Replay]
+fn start_playback(
+    mut commands: Commands,
+    mut board_changed: EventWriter<BoardChanged>,
+    mut game: ResMut<game::Game>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut replay: ResMut<Replay>,
+    game_over_query: Query<Entity, With<GameOver>>,
+) {
+    for entity in game_over_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    replay.moves = game.history().to_vec();
+    *game = replay.board_at(0);
+    *playback = ReplayPlayback::default();
+    board_changed.send(BoardChanged);
+}
+
+/// Makes the next move of the [Replay] every [REPLAY_STEP] seconds
+fn step_playback(
+    time: Res<Time>,
+    mut state: ResMut<State<GameState>>,
+    mut board_changed: EventWriter<BoardChanged>,
+    mut game: ResMut<game::Game>,
+    mut playback: ResMut<ReplayPlayback>,
+    settings: Res<Settings>,
+    replay: Res<Replay>,
+) {
+    if !playback.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    match replay.moves.get(playback.step) {
+        Some(m) => {
+            game.apply_move(*m);
+            reveal_flags_on_win(&mut game, &settings);
+            playback.step += 1;
+            board_changed.send(BoardChanged);
+        }
+        None => {
+            playback.finished = true;
+            state
+                .set(GameState::Over)
+                .expect("Failed to change the game state");
+        }
+    }
+}
+
+/// Forgets the finished [Replay] once the game that is over is left
+fn reset_playback(mut playback: ResMut<ReplayPlayback>) {
+    *playback = ReplayPlayback::default();
+}
+
+/// How fast the camera moves towards its new focus point
+const FOCUS_SPEED: f32 = 5.;
+
+/// Maps a numpad digit to the center of one of the 3x3 board regions
+///
+/// 1 is the bottom left region (closest to the initial camera position),
+/// 5 is the center of the board and 9 is the top right region.
+fn quadrant_focus(digit: u8, w: u8, h: u8) -> Vec3 {
+    let digit = digit.clamp(1, 9) - 1;
+    let column = (digit % 3) as f32 - 1.;
+    let row = (digit / 3) as f32 - 1.;
+
+    Vec3::new(column * w as f32 / 3., 0., -row * h as f32 / 3.) * tile_scale(w, h)
+}
+
+/// Moves the camera focus to a board region when a [KeyAction::Focus] key is pressed
+fn focus_camera(
+    time: Res<Time>,
+    bindings: Res<KeyBindings>,
+    keys: Res<Input<KeyCode>>,
+    game: Res<game::Game>,
+    mut query: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let target = (1..=9)
+        .find(|digit| bindings.just_pressed(&keys, KeyAction::Focus(*digit)))
+        .map(|digit| quadrant_focus(digit, game.width(), game.height()));
+
+    for (mut orbit, mut transform) in query.iter_mut() {
+        if target.is_some() {
+            orbit.target = target;
+        }
 
-    state
-        .set(GameState::Playing)
-        .expect("Failed to reset the game state");
-}
+        if let Some(target) = orbit.target {
+            let step = (time.delta_seconds() * FOCUS_SPEED).min(1.);
+            orbit.focus = orbit.focus.lerp(target, step);
+            if orbit.focus.distance_squared(target) < 1e-4 {
+                orbit.focus = target;
+                orbit.target = None;
+            }
 
-/// Displays the score when the game is over.
-fn game_over(mut commands: Commands, game: Res<game::Game>, game_materials: Res<GameMaterials>) {
-    commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                margin: Rect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            material: game_materials.transparent.clone(),
-            ..Default::default()
-        })
-        .insert(GameOver)
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::with_section(
-                    format!(
-                        "Game over. {}",
-                        match game.state() {
-                            game::GameState::Won => "You won!",
-                            game::GameState::Lost => "You lost!",
-                            _ => unreachable!(),
-                        }
-                    ),
-                    TextStyle {
-                        font: game_materials.notification_font.clone(),
-                        font_size: 80.0,
-                        color: Color::BLACK,
-                    },
-                    Default::default(),
-                ),
-                ..Default::default()
-            });
-        });
+            let rot_matrix = Mat3::from_quat(transform.rotation);
+            transform.translation =
+                orbit.focus + rot_matrix.mul_vec3(Vec3::new(0.0, 0.0, orbit.radius));
+        }
+    }
 }
 
 /// Orbits camera (only 'yaw').
@@ -903,6 +3120,86 @@ fn orbit_camera(
     }
 }
 
+/// Displays the FPS and entity counts, toggled with [KeyAction::DebugOverlay]
+#[cfg(feature = "debug_overlay")]
+struct DebugOverlay;
+
+#[cfg(feature = "debug_overlay")]
+fn debug_overlay_text(fps: Option<f64>, entities: Option<f64>, tiles: usize) -> String {
+    let value = |value: Option<f64>| {
+        value
+            .map(|value| format!("{:.0}", value))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    format!(
+        "FPS: {} Entities: {} Tiles: {}",
+        value(fps),
+        value(entities),
+        tiles
+    )
+}
+
+#[cfg(feature = "debug_overlay")]
+fn toggle_debug_overlay(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    keys: Res<Input<KeyCode>>,
+    materials: Res<GameMaterials>,
+    overlay_query: Query<Entity, With<DebugOverlay>>,
+) {
+    if !bindings.just_pressed(&keys, KeyAction::DebugOverlay) {
+        return;
+    }
+
+    if let Some(entity) = overlay_query.iter().next() {
+        commands.entity(entity).despawn_recursive();
+        return;
+    }
+
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: materials.text_font.clone(),
+                    font_size: 15.0,
+                    color: Color::YELLOW,
+                },
+                Default::default(),
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.),
+                    right: Val::Px(5.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(DebugOverlay);
+}
+
+#[cfg(feature = "debug_overlay")]
+fn update_debug_overlay(
+    diagnostics: Res<Diagnostics>,
+    tile_query: Query<&Tile>,
+    mut text_query: Query<&mut Text, With<DebugOverlay>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = debug_overlay_text(
+            diagnostics
+                .get(FrameTimeDiagnosticsPlugin::FPS)
+                .and_then(|fps| fps.average()),
+            diagnostics
+                .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+                .and_then(|count| count.value()),
+            tile_query.iter().count(),
+        );
+    }
+}
+
 #[wasm_bindgen]
 pub fn run() {
     let mut app = App::build();
@@ -911,7 +3208,6 @@ pub fn run() {
             title: "Minesweeper".to_string(),
             width: 720.,
             height: 720.,
-            resizable: false,
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
@@ -923,5 +3219,913 @@ pub fn run() {
     #[cfg(target_arch = "wasm32")]
     app.add_plugin(bevy_webgl2::WebGL2Plugin);
 
+    #[cfg(feature = "debug_overlay")]
+    app.add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(EntityCountDiagnosticsPlugin)
+        .add_system(toggle_debug_overlay.system())
+        .add_system(update_debug_overlay.system());
+
     app.run()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::app::Events;
+
+    #[test]
+    fn left_click() {
+        use game::CellState::*;
+
+        assert_eq!(left_click_action(Uncovered, true, true), TileAction::Chord);
+        assert_eq!(left_click_action(Uncovered, true, false), TileAction::Open);
+        assert_eq!(left_click_action(Uncovered, false, true), TileAction::Open);
+        assert_eq!(left_click_action(Covered, false, true), TileAction::Open);
+        assert_eq!(left_click_action(Flagged, false, true), TileAction::Open);
+    }
+
+    #[test]
+    fn right_click() {
+        use game::CellState::*;
+
+        assert_eq!(right_click_action(Uncovered, true, true), TileAction::Chord);
+        assert_eq!(right_click_action(Uncovered, true, false), TileAction::Flag);
+        assert_eq!(right_click_action(Uncovered, false, true), TileAction::Flag);
+        assert_eq!(right_click_action(Covered, false, true), TileAction::Flag);
+        assert_eq!(right_click_action(Flagged, false, true), TileAction::Flag);
+        assert_eq!(
+            right_click_action(Questioned, false, true),
+            TileAction::Flag
+        );
+    }
+
+    #[test]
+    fn scenes() {
+        use game::CellState::*;
+        use game::GameState::*;
+
+        // While playing mines stay hidden
+        assert_eq!(tile_scene(Covered, false, Continue), TileScene::Trees);
+        assert_eq!(tile_scene(Covered, true, Continue), TileScene::Trees);
+        assert_eq!(tile_scene(Questioned, true, Continue), TileScene::Trees);
+        assert_eq!(tile_scene(Uncovered, false, Continue), TileScene::Empty);
+        assert_eq!(tile_scene(Flagged, false, Continue), TileScene::Flag);
+        assert_eq!(tile_scene(Flagged, true, Continue), TileScene::Flag);
+
+        // Won: flags stay, mines stay hidden
+        assert_eq!(tile_scene(Flagged, true, Won), TileScene::Flag);
+        assert_eq!(tile_scene(Covered, true, Won), TileScene::Trees);
+        assert_eq!(tile_scene(Uncovered, false, Won), TileScene::Empty);
+
+        // Lost: all mines are revealed, flagged or not
+        assert_eq!(tile_scene(Covered, true, Lost), TileScene::Mine);
+        assert_eq!(tile_scene(Flagged, true, Lost), TileScene::Mine);
+        assert_eq!(tile_scene(Flagged, false, Lost), TileScene::Flag);
+        assert_eq!(tile_scene(Covered, false, Lost), TileScene::Trees);
+    }
+
+    #[test]
+    fn low_spec() {
+        // A freshly set up 30x16 board
+        let tiles = 30 * 16;
+        let scenes = |low_spec| tiles * scene_layers(TileScene::Trees, low_spec).len();
+        assert_eq!(scenes(false), 480);
+        assert_eq!(scenes(true), 0);
+
+        assert_eq!(scene_layers(TileScene::Empty, true), &[]);
+        assert_eq!(scene_layers(TileScene::Flag, true), &[TileScene::Flag]);
+        assert_eq!(scene_layers(TileScene::Mine, true), &[TileScene::Mine]);
+    }
+
+    #[test]
+    fn flat_uncovered() {
+        // A cascade uncovering 100 tiles
+        let scenes = |flat_uncovered| {
+            let settings = Settings {
+                flat_uncovered,
+                ..Default::default()
+            };
+            100 * tile_layers(TileScene::Empty, &settings).len()
+        };
+        assert_eq!(scenes(false), 100);
+        assert_eq!(scenes(true), 0);
+
+        let settings = Settings {
+            flat_uncovered: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tile_layers(TileScene::Flag, &settings),
+            &[TileScene::Empty, TileScene::Flag]
+        );
+        assert_eq!(tile_offset(TileScene::Empty, &settings), -FLAT_DEPTH);
+        assert_eq!(tile_offset(TileScene::Trees, &settings), 0.);
+        assert_eq!(tile_offset(TileScene::Empty, &Settings::default()), 0.);
+    }
+
+    #[test]
+    fn materials() {
+        use game::CellState::*;
+
+        assert_eq!(tile_material(Covered), TileMaterial::Covered);
+        assert_eq!(tile_material(Flagged), TileMaterial::Covered);
+        assert_eq!(tile_material(Uncovered), TileMaterial::Uncovered);
+        assert_eq!(tile_material(Questioned), TileMaterial::Questioned);
+    }
+
+    #[test]
+    fn tutorial() {
+        let mut game = tutorial_game();
+        let mut progress = TutorialProgress::default();
+
+        // Anything but the current step is ignored
+        assert!(!progress.advance(TutorialAction::Flag, 0, 4));
+        assert!(!progress.advance(TutorialAction::Open, 1, 4));
+        assert_eq!(progress, TutorialProgress(0));
+
+        for step in TUTORIAL_STEPS.iter() {
+            assert_eq!(game.state(), game::GameState::Continue);
+            assert_eq!(progress.prompt(), step.prompt);
+            assert!(progress.advance(step.action, step.x, step.y));
+            assert!(match step.action {
+                TutorialAction::Open => game.open(step.x, step.y),
+                TutorialAction::Flag => game.flag(step.x, step.y).is_some(),
+                TutorialAction::Chord => game.chord(step.x, step.y),
+            });
+        }
+
+        // Every step is needed to clear the board
+        assert_eq!(game.state(), game::GameState::Won);
+        assert!(progress.step().is_none());
+        assert!(!progress.advance(TutorialAction::Open, 0, 0));
+    }
+
+    #[test]
+    fn mines_left() {
+        let mut cells = vec![game::GameCell::default(); 4];
+        cells[0].mine = true;
+        cells[3].mine = true;
+        let mut game = game::Game::from_cells(2, 2, cells).unwrap();
+        game.flag(1, 0);
+
+        assert_eq!(mines_left_label(&game, true), "Left: 1");
+        assert_eq!(mines_left_label(&game, false), "Left: 1");
+
+        game.open(0, 1);
+        game.open(0, 0);
+        assert!(game.is_lost());
+        assert_eq!(mines_left_label(&game, true), "Left: 1 (really 2)");
+        assert_eq!(mines_left_label(&game, false), "Left: 1");
+
+        game.flag(0, 0);
+        game.flag(1, 1);
+        assert_eq!(mines_left_label(&game, false), "Left: -1");
+    }
+
+    #[test]
+    fn difficulty_mines_left() {
+        for (level, mines) in [
+            (DifficultyLevel::Easy, 6),
+            (DifficultyLevel::Medium, 25),
+            (DifficultyLevel::Hard, 56),
+        ] {
+            let game = generate_game(level.params(), Symmetry::Off);
+            assert_eq!(game.mines(), mines);
+            assert_eq!(game.params(), level.params());
+            assert_eq!(mines_left_label(&game, false), format!("Left: {}", mines));
+        }
+    }
+
+    #[test]
+    fn focus() {
+        assert_eq!(quadrant_focus(5, 9, 9), Vec3::ZERO);
+        assert_eq!(quadrant_focus(1, 9, 6), Vec3::new(-3., 0., 2.));
+        assert_eq!(quadrant_focus(3, 9, 6), Vec3::new(3., 0., 2.));
+        assert_eq!(quadrant_focus(8, 9, 6), Vec3::new(0., 0., -2.));
+        assert_eq!(quadrant_focus(9, 9, 6), Vec3::new(3., 0., -2.));
+        // Out of range digits stick to the closest region
+        assert_eq!(quadrant_focus(0, 9, 6), quadrant_focus(1, 9, 6));
+        assert_eq!(quadrant_focus(10, 9, 6), quadrant_focus(9, 9, 6));
+        // Regions follow the scaled tiles
+        assert_eq!(
+            quadrant_focus(9, 40, 40),
+            Vec3::new(40. / 3., 0., -40. / 3.) * 2.
+        );
+    }
+
+    #[test]
+    fn replay() {
+        let mut cells = vec![game::GameCell::default(); 4 * 4];
+        cells[0].mine = true;
+        cells[5].mine = true;
+        let mut game = game::Game::from_cells(4, 4, cells).unwrap();
+        let mut replay = Replay::new(game.clone());
+
+        let moves = [
+            Move::Flag(0, 0),
+            Move::Open(3, 3),
+            Move::Flag(1, 1),
+            Move::Chord(2, 0),
+            Move::Open(0, 1),
+        ];
+        for m in moves.iter() {
+            assert!(game.apply_move(*m).changed());
+        }
+        assert_eq!(game.state(), game::GameState::Won);
+        assert_eq!(game.history(), moves);
+        replay.moves = game.history().to_vec();
+
+        assert_eq!(replay.board_at(0), replay.initial);
+        assert_eq!(replay.board_at(moves.len()), game);
+        // Replaying doesn't depend on anything but the moves
+        assert_eq!(replay.board_at(moves.len()), replay.board_at(moves.len()));
+        assert_eq!(replay.board_at(100), game);
+    }
+
+    #[test]
+    fn adaptive_assist() {
+        assert_eq!(Assist::for_streak(0), Assist::None);
+        assert_eq!(Assist::for_streak(1), Assist::None);
+        assert_eq!(Assist::for_streak(2), Assist::SafeTile);
+        assert_eq!(Assist::for_streak(3), Assist::OpenRegion);
+        assert_eq!(Assist::for_streak(10), Assist::OpenRegion);
+
+        let easy = DifficultyLevel::Easy.params();
+        let hard = DifficultyLevel::Hard.params();
+        let mut stats = SessionStats::default();
+        assert_eq!(stats.loss_streak(easy), 0);
+        stats.record(easy, true);
+        stats.record(easy, true);
+        assert_eq!(stats.loss_streak(easy), 2);
+        assert_eq!(stats.loss_streak(hard), 0);
+        // Only losses in a row on the same boards count
+        stats.record(hard, true);
+        assert_eq!(stats.loss_streak(hard), 1);
+        assert_eq!(stats.loss_streak(easy), 0);
+        stats.record(hard, false);
+        assert_eq!(stats.loss_streak(hard), 0);
+
+        let mut cells = vec![game::GameCell::default(); 4 * 4];
+        cells[0].mine = true;
+        cells[5].mine = true;
+        let board = game::Game::from_cells(4, 4, cells).unwrap();
+
+        let mut game = board.clone();
+        Assist::None.apply(&mut game);
+        assert_eq!(game, board);
+
+        let mut game = board.clone();
+        Assist::SafeTile.apply(&mut game);
+        assert!(game.has_started());
+        assert_eq!(game.state(), game::GameState::Continue);
+        assert_eq!(game.history().len(), 1);
+
+        let mut game = board.clone();
+        Assist::OpenRegion.apply(&mut game);
+        assert_eq!(game.cell_state(3, 3), Some(game::CellState::Uncovered));
+        assert_eq!(game.cell_state(2, 1), Some(game::CellState::Uncovered));
+        assert_eq!(game.cell_state(1, 0), Some(game::CellState::Covered));
+        // Both assists are moves, replayed from the untouched board
+        assert_eq!(game.history().len(), 1);
+        let mut replay = Replay::new(board);
+        replay.moves = game.history().to_vec();
+        assert_eq!(replay.board_at(1), game);
+    }
+
+    #[test]
+    fn accuracy() {
+        // Nothing to be right or wrong about yet
+        assert_eq!(flag_accuracy(0, 0), None);
+        assert_eq!(flag_accuracy(0, 3), Some(0));
+        assert_eq!(flag_accuracy(2, 3), Some(66));
+        assert_eq!(flag_accuracy(4, 4), Some(100));
+
+        let mut cells = vec![game::GameCell::default(); 3 * 3];
+        cells[0].mine = true;
+        let mut game = game::Game::from_cells(3, 3, cells).unwrap();
+        assert_eq!(flag_accuracy_label(&game), "Flags right: - Covered: 9");
+        game.flag(0, 0);
+        game.flag(1, 0);
+        assert_eq!(flag_accuracy_label(&game), "Flags right: 50% Covered: 7");
+    }
+
+    #[test]
+    fn high_scores() {
+        let easy = DifficultyLevel::Easy.params();
+        let hard = DifficultyLevel::Hard.params();
+        let mut scores = HighScores::default();
+
+        // The first win is a record
+        assert!(scores.record(easy, 60));
+        // Beats
+        assert!(scores.record(easy, 45));
+        // Ties
+        assert!(!scores.record(easy, 45));
+        // Doesn't beat
+        assert!(!scores.record(easy, 50));
+        assert_eq!(scores.best[&easy], 45);
+        // Every difficulty has its own record
+        assert!(scores.record(hard, 300));
+
+        assert_eq!(game_over_message(true, Some(45)), "New record: 45s!");
+        assert_eq!(game_over_message(true, None), "Game over. You won!");
+        assert_eq!(game_over_message(false, None), "Game over. You lost!");
+    }
+
+    #[test]
+    fn tooltip() {
+        let mut cells = vec![game::GameCell::default(); 4 * 4];
+        cells[0].mine = true;
+        cells[5].mine = true;
+        let mut game = game::Game::from_cells(4, 4, cells).unwrap();
+
+        // Covered tiles don't give anything away
+        assert_eq!(tile_tooltip(&game, 0, 0), "(0,0)");
+        assert_eq!(tile_tooltip(&game, 2, 1), "(2,1)");
+        game.flag(0, 0);
+        assert_eq!(tile_tooltip(&game, 0, 0), "(0,0)");
+
+        game.open(2, 1);
+        assert_eq!(tile_tooltip(&game, 2, 1), "(2,1): 1 mine, to flag: 1");
+        game.open(1, 0);
+        assert_eq!(tile_tooltip(&game, 1, 0), "(1,0): 2 mines, to flag: 1");
+        game.open(3, 3);
+        assert_eq!(tile_tooltip(&game, 3, 3), "(3,3): 0 mines, to flag: 0");
+    }
+
+    #[test]
+    fn win_reveals_flags() {
+        let mut cells = vec![game::GameCell::default(); 3 * 3];
+        cells[0].mine = true;
+        cells[8].mine = true;
+        let board = game::Game::from_cells(3, 3, cells).unwrap();
+        let clicks = || vec![Click::Button(MouseButton::Left)].into_iter();
+
+        let mut settings = Settings::default();
+        assert!(settings.win_reveals_flags);
+        let mut game = board.clone();
+        apply_clicks(&mut game, &settings, clicks(), 2, 0);
+        assert_eq!(game.unflagged_mines(), 2);
+        apply_clicks(&mut game, &settings, clicks(), 0, 2);
+        assert!(game.is_won());
+        assert_eq!(game.unflagged_mines(), 0);
+        assert_eq!(game.cell_state(0, 0), Some(game::CellState::Flagged));
+        assert_eq!(game.cell_state(2, 2), Some(game::CellState::Flagged));
+
+        // The replay ends on the same board
+        let mut replayed = board.clone();
+        for m in game.history() {
+            replayed.apply_move(*m);
+            reveal_flags_on_win(&mut replayed, &settings);
+        }
+        assert_eq!(replayed, game);
+
+        // The mines stay hidden otherwise
+        settings.win_reveals_flags = false;
+        let mut game = board.clone();
+        apply_clicks(&mut game, &settings, clicks(), 2, 0);
+        apply_clicks(&mut game, &settings, clicks(), 0, 2);
+        assert!(game.is_won());
+        assert_eq!(game.unflagged_mines(), 2);
+    }
+
+    #[test]
+    fn replay_auto_complete() {
+        // *..
+        // ...
+        // ..*
+        let mut cells = vec![game::GameCell::default(); 3 * 3];
+        cells[0].mine = true;
+        cells[8].mine = true;
+        let clicks = || vec![Click::Button(MouseButton::Left)].into_iter();
+        let settings = Settings {
+            auto_complete: true,
+            win_reveals_flags: false,
+            ..Default::default()
+        };
+
+        for &condition in &[
+            game::WinCondition::FlagAllMines,
+            game::WinCondition::OpenAllSafe,
+        ] {
+            let mut board = game::Game::from_cells(3, 3, cells.clone()).unwrap();
+            board.set_win_condition(condition);
+            let mut game = board.clone();
+            apply_clicks(&mut game, &settings, clicks(), 2, 0);
+            apply_clicks(&mut game, &settings, clicks(), 0, 2);
+            assert!(game.is_won());
+            assert_eq!(game.unflagged_mines(), 0);
+
+            let mut replayed = board;
+            for m in game.history() {
+                replayed.apply_move(*m);
+                reveal_flags_on_win(&mut replayed, &settings);
+            }
+            assert_eq!(replayed, game);
+        }
+    }
+
+    #[test]
+    fn click_queue() {
+        use MouseButton::*;
+
+        let mut cells = vec![game::GameCell::default(); 4 * 4];
+        cells[0].mine = true;
+        cells[5].mine = true;
+        let mut game = game::Game::from_cells(4, 4, cells).unwrap();
+        let settings = Settings::default();
+
+        // Several presses within a single frame
+        let mut queue = ClickQueue::default();
+        for &button in &[Right, Right, Middle, Left, Left, Right] {
+            queue.push(button);
+        }
+
+        // Each press is handled in order, the ones that don't change
+        // anything are skipped
+        let moves = apply_clicks(&mut game, &settings, queue.drain(), 3, 3);
+        assert_eq!(
+            moves,
+            [Move::Flag(3, 3), Move::Flag(3, 3), Move::Open(3, 3)]
+        );
+        assert!(queue.clicks.is_empty());
+        assert_eq!(game.cell_state(3, 3), Some(game::CellState::Uncovered));
+
+        // Nothing is applied once the game is over
+        queue.push(Left);
+        queue.push(Right);
+        let moves = apply_clicks(&mut game, &settings, queue.drain(), 0, 0);
+        assert_eq!(moves, [Move::Open(0, 0)]);
+        assert!(game.is_lost());
+        assert_eq!(game.cell_state(0, 0), Some(game::CellState::Covered));
+    }
+
+    #[test]
+    fn both_buttons() {
+        use game::CellState::*;
+        use MouseButton::*;
+
+        assert_eq!(chord_click_action(Uncovered, true), Some(TileAction::Chord));
+        assert_eq!(chord_click_action(Uncovered, false), None);
+        assert_eq!(chord_click_action(Covered, false), None);
+        assert_eq!(chord_click_action(Flagged, false), None);
+
+        // A single button clicks once released
+        let mut queue = ClickQueue::default();
+        queue.press(Left);
+        assert!(queue.clicks.is_empty());
+        queue.release(Left);
+        assert_eq!(queue.drain().collect::<Vec<_>>(), [Click::Button(Left)]);
+
+        // Both pressed together chord, releasing them doesn't click
+        queue.press(Left);
+        queue.press(Right);
+        assert_eq!(queue.clicks, [Click::Both]);
+        queue.release(Left);
+        queue.release(Right);
+        assert_eq!(queue.drain().collect::<Vec<_>>(), [Click::Both]);
+
+        // Even with the second button pressed a frame later
+        queue.press(Right);
+        assert_eq!(queue.drain().count(), 0);
+        queue.press(Left);
+        queue.release(Right);
+        queue.release(Left);
+        assert_eq!(queue.drain().collect::<Vec<_>>(), [Click::Both]);
+
+        // Once released, the buttons click on their own again
+        queue.press(Right);
+        queue.release(Right);
+        queue.press(Left);
+        queue.release(Left);
+        assert_eq!(
+            queue.drain().collect::<Vec<_>>(),
+            [Click::Button(Right), Click::Button(Left)]
+        );
+
+        // Dragging orbits the camera instead of clicking, a shaky hand doesn't
+        queue.press(Left);
+        queue.drag(Vec2::new(30., 40.));
+        queue.release(Left);
+        assert!(queue.clicks.is_empty());
+        queue.press(Left);
+        queue.drag(Vec2::new(1., -2.));
+        queue.release(Left);
+        assert_eq!(queue.drain().collect::<Vec<_>>(), [Click::Button(Left)]);
+
+        // Forgotten buttons don't click, nor do the ones never pressed
+        queue.press(Left);
+        queue.forget(Left);
+        queue.release(Left);
+        queue.release(Right);
+        assert!(queue.clicks.is_empty());
+
+        // 1.
+        // F.
+        let mut cells = vec![game::GameCell::default(); 4];
+        cells[2].mine = true;
+        let mut game = game::Game::from_cells(2, 2, cells).unwrap();
+        game.open(0, 0);
+        game.flag(0, 1);
+        let board = game.clone();
+        let moves = apply_clicks(
+            &mut game,
+            &Settings::default(),
+            vec![Click::Both].into_iter(),
+            0,
+            0,
+        );
+        assert_eq!(moves, [Move::Chord(0, 0)]);
+        assert!(game.is_won());
+
+        // The middle button chords too
+        let mut game = board;
+        let clicks = vec![Click::Button(Middle), Click::Button(Middle)];
+        let moves = apply_clicks(&mut game, &Settings::default(), clicks.into_iter(), 0, 0);
+        assert_eq!(moves, [Move::Chord(0, 0)]);
+        assert!(game.is_won());
+    }
+
+    #[cfg(feature = "debug_overlay")]
+    #[test]
+    fn debug_overlay() {
+        assert_eq!(
+            debug_overlay_text(Some(59.6), Some(1234.), 225),
+            "FPS: 60 Entities: 1234 Tiles: 225"
+        );
+        assert_eq!(
+            debug_overlay_text(None, None, 0),
+            "FPS: - Entities: - Tiles: 0"
+        );
+    }
+
+    #[test]
+    fn timer() {
+        use game::GameState::*;
+
+        assert!(!timer_running(false, Continue));
+        assert!(timer_running(true, Continue));
+        assert!(!timer_running(true, Won));
+        assert!(!timer_running(true, Lost));
+    }
+
+    #[test]
+    fn minimap() {
+        use game::CellState::*;
+
+        let states = [Covered, Uncovered, Flagged, Questioned];
+        for (i, a) in states.iter().enumerate() {
+            // Hovering stands out, whatever the state
+            assert_eq!(minimap_color(*a, true), Color::WHITE);
+            // Every state has its own color
+            for b in states.iter().skip(i + 1) {
+                assert_ne!(minimap_color(*a, false), minimap_color(*b, false));
+            }
+            assert_ne!(minimap_color(*a, false), Color::WHITE);
+        }
+    }
+
+    #[test]
+    fn auto_restart() {
+        use std::time::Duration;
+
+        let second = Duration::from_secs(1);
+
+        // Disabled by default
+        let mut auto_restart = AutoRestart::default();
+        assert!(!auto_restart.tick(second, false));
+
+        let mut auto_restart = AutoRestart::new(true, 3.);
+        assert!(!auto_restart.tick(second, false));
+        assert!(!auto_restart.tick(second, false));
+        assert!(auto_restart.tick(second, false));
+        // Restarts only once
+        assert!(!auto_restart.tick(second, false));
+
+        // Any input cancels the countdown for good
+        let mut auto_restart = AutoRestart::new(true, 3.);
+        assert!(!auto_restart.tick(second, false));
+        assert!(!auto_restart.tick(second, true));
+        assert!(!auto_restart.tick(second, false));
+        assert!(!auto_restart.tick(second * 5, false));
+
+        // A hand-edited negative delay doesn't crash
+        let mut auto_restart = AutoRestart::new(true, -1.);
+        assert!(auto_restart.tick(second, false));
+    }
+
+    #[test]
+    fn board_dirty() {
+        let mut world = World::default();
+        world.insert_resource(Events::<BoardChanged>::default());
+        world.insert_resource(BoardDirty::default());
+        let mut stage = SystemStage::parallel();
+        stage.add_system(track_board_changes.system());
+
+        // No changes - nothing to render
+        stage.run(&mut world);
+        assert!(!world.get_resource::<BoardDirty>().unwrap().0);
+
+        world
+            .get_resource_mut::<Events<BoardChanged>>()
+            .unwrap()
+            .send(BoardChanged);
+        world
+            .get_resource_mut::<Events<BoardChanged>>()
+            .unwrap()
+            .send(BoardChanged);
+        stage.run(&mut world);
+        assert!(world.get_resource::<BoardDirty>().unwrap().0);
+
+        // Rendering clears the flag, the same events aren't counted twice
+        world.get_resource_mut::<BoardDirty>().unwrap().0 = false;
+        stage.run(&mut world);
+        assert!(!world.get_resource::<BoardDirty>().unwrap().0);
+    }
+
+    #[test]
+    fn finished_replay_not_recorded() {
+        let mut cells = vec![game::GameCell::default(); 2 * 2];
+        cells[0].mine = true;
+        let mut game = game::Game::from_cells(2, 2, cells).unwrap();
+        game.open(1, 1);
+        game.open(0, 0);
+        assert!(game.is_lost());
+        let params = game.params();
+
+        let mut world = World::default();
+        world.insert_resource(game);
+        world.insert_resource(SessionStats::default());
+        world.insert_resource(ReplayPlayback::default());
+        let mut stage = SystemStage::parallel();
+        stage.add_system(record_game.system());
+
+        stage.run(&mut world);
+        assert_eq!(
+            world
+                .get_resource::<SessionStats>()
+                .unwrap()
+                .loss_streak(params),
+            1
+        );
+
+        // The same game once its replay is over
+        world.get_resource_mut::<ReplayPlayback>().unwrap().finished = true;
+        stage.run(&mut world);
+        assert_eq!(
+            world
+                .get_resource::<SessionStats>()
+                .unwrap()
+                .loss_streak(params),
+            1
+        );
+    }
+
+    #[test]
+    fn ui_camera_survives_cleanup() {
+        let mut world = World::default();
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(cleanup_menu.system())
+            .add_system(cleanup_ui.system())
+            .add_system(cleanup_camera.system());
+
+        world.spawn().insert(UICamera);
+        for _ in 0..3 {
+            world.spawn().insert(MenuUI);
+            world.spawn().insert(GameUI);
+            world.spawn().insert(BackButton);
+            stage.run(&mut world);
+
+            assert_eq!(world.query::<&UICamera>().iter(&world).count(), 1);
+            assert_eq!(world.query::<&MenuUI>().iter(&world).count(), 0);
+            assert_eq!(world.query::<&GameUI>().iter(&world).count(), 0);
+        }
+    }
+
+    #[test]
+    fn brightness_clamp() {
+        let mut settings = Settings::default();
+
+        settings.set_brightness(0.5);
+        assert_eq!(settings.brightness, 0.5);
+        settings.set_brightness(-1.);
+        assert_eq!(settings.brightness, Settings::MIN_BRIGHTNESS);
+        settings.set_brightness(100.);
+        assert_eq!(settings.brightness, Settings::MAX_BRIGHTNESS);
+
+        // Saved settings are clamped too
+        #[cfg(feature = "serde")]
+        for json in &[r#"{"brightness": 0}"#, r#"{"brightness": -3}"#] {
+            let loaded = Settings::parse(json).unwrap();
+            assert_eq!(loaded.brightness, Settings::MIN_BRIGHTNESS);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn settings() {
+        // Defaults match the behavior before the settings were introduced
+        let settings = Settings::default();
+        assert_eq!(settings.brightness, 1.0 / 5.0);
+        assert!(!settings.auto_chord);
+        assert!(!settings.question_marks);
+        assert_eq!(settings.symmetry, Symmetry::Off);
+        assert_eq!(settings.win_condition, game::WinCondition::OpenAllSafe);
+
+        let settings = Settings {
+            brightness: 0.5,
+            auto_chord: true,
+            question_marks: true,
+            symmetry: Symmetry::Rotational,
+            auto_restart: true,
+            auto_restart_delay: 3.,
+            true_mines_left: true,
+            low_spec: true,
+            auto_complete: true,
+            right_click_chords: true,
+            minimap: true,
+            win_condition: game::WinCondition::FlagAllMines,
+            flip_vertical: true,
+            adaptive_assist: true,
+            mulligan: true,
+            win_reveals_flags: false,
+            keys: KeyBindings {
+                restart: KeyCode::F5,
+                ..Default::default()
+            },
+            flag_accuracy: true,
+            flat_uncovered: true,
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+
+        // Missing settings fall back to their defaults
+        let settings: Settings = serde_json::from_str(r#"{"auto_chord":true}"#).unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                auto_chord: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn key_bindings() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.key(KeyAction::Focus(1)), KeyCode::Key1);
+        assert_eq!(bindings.key(KeyAction::Focus(9)), KeyCode::Key9);
+        assert_eq!(bindings.key(KeyAction::Restart), KeyCode::R);
+        assert_eq!(bindings.key(KeyAction::Hint), KeyCode::H);
+        assert_eq!(bindings.debug_overlay, KeyCode::F3);
+
+        // Remapped in the settings, the rest keeps the defaults
+        let settings: Settings = serde_json::from_str(
+            r#"{"keys":{"hint":"Space","focus":["Q","W","E","A","S","D","Z","X","C"]}}"#,
+        )
+        .unwrap();
+        let bindings = settings.keys;
+        assert_eq!(bindings.key(KeyAction::Hint), KeyCode::Space);
+        assert_eq!(bindings.key(KeyAction::Focus(5)), KeyCode::S);
+        assert_eq!(bindings.key(KeyAction::Restart), KeyCode::R);
+
+        let mut keys = Input::default();
+        keys.press(KeyCode::Space);
+        assert!(bindings.just_pressed(&keys, KeyAction::Hint));
+        assert!(!KeyBindings::default().just_pressed(&keys, KeyAction::Hint));
+        keys.press(KeyCode::Key5);
+        assert!(!bindings.just_pressed(&keys, KeyAction::Focus(5)));
+    }
+
+    #[test]
+    fn tile_placement() {
+        // Small boards keep unit sized tiles
+        assert_eq!(tile_scale(5, 5), 1.);
+        assert_eq!(tile_scale(10, 4), 1.);
+        assert_eq!(tile_position(5, 5, 0, 0, false), Vec3::new(-2.5, 0., -2.5));
+        assert_eq!(tile_position(5, 5, 3, 4, false), Vec3::new(0.5, 0., 1.5));
+        // Larger ones get bigger tiles, growing slower than the board
+        assert_eq!(tile_scale(40, 20), 2.);
+        assert!(tile_scale(15, 15) > 1.);
+        assert!(tile_scale(30, 16) > tile_scale(15, 15));
+        assert!(tile_scale(30, 16) < 2. * tile_scale(15, 15));
+        assert_eq!(
+            tile_position(40, 20, 0, 0, false),
+            Vec3::new(-40., 0., -20.)
+        );
+        // Flipping only moves the rows
+        assert_eq!(tile_position(5, 5, 3, 4, true), Vec3::new(0.5, 0., -2.5));
+    }
+
+    #[test]
+    fn row_placement() {
+        // Row 0 is at the back by default...
+        assert_eq!(row_z(5, 0, false), -2.5);
+        assert_eq!(row_z(5, 4, false), 1.5);
+        // ...and at the front when flipped, taking the place of the last row
+        assert_eq!(row_z(5, 0, true), 1.5);
+        assert_eq!(row_z(5, 4, true), -2.5);
+        assert_eq!(row_z(4, 1, true), row_z(4, 2, false));
+        for y in 0..7 {
+            assert_eq!(row_z(7, y, true), row_z(7, 6 - y, false));
+        }
+    }
+
+    /// Checks if two rectangles share any area. Touching edges don't count
+    fn overlaps(a: &UiRect, b: &UiRect) -> bool {
+        a.left < b.left + b.width
+            && b.left < a.left + a.width
+            && a.top < b.top + b.height
+            && b.top < a.top + a.height
+    }
+
+    #[test]
+    fn ui_layout() {
+        // The default window keeps the original proportions
+        let layout = UiLayout::new(720., 720., false);
+        assert_eq!(layout.bar, 72.);
+        assert_eq!(layout.font_size, 40.);
+        assert_eq!(layout.rect(UiSlot::Restart).left, 324.);
+        assert_eq!(UiLayout::new(720., 720., true), layout);
+
+        // The accuracy row only shrinks the bars when it's shown
+        assert_eq!(UiLayout::new(800., 60., false).bar, MIN_BAR_HEIGHT);
+        assert!(UiLayout::new(800., 60., true).bar < MIN_BAR_HEIGHT);
+
+        let slots = [
+            UiSlot::Timer,
+            UiSlot::Restart,
+            UiSlot::MinesLeft,
+            UiSlot::Back,
+            UiSlot::Hint,
+            UiSlot::Accuracy,
+        ];
+        for &(width, height, accuracy_row) in &[
+            (720., 720., true),
+            (1920., 1080., true),
+            (320., 240., true),
+            (200., 800., true),
+            (800., 100., true),
+            (800., 60., false),
+            (40., 40., true),
+            (40., 40., false),
+        ] {
+            let layout = UiLayout::new(width, height, accuracy_row);
+            let window = UiRect {
+                left: 0.,
+                top: 0.,
+                width,
+                height,
+            };
+            // The accuracy row is the last one
+            let slots = if accuracy_row {
+                &slots[..]
+            } else {
+                &slots[..slots.len() - 1]
+            };
+            for (i, a) in slots.iter().enumerate() {
+                let rect = layout.rect(*a);
+                assert!(rect.width >= 0. && rect.height >= 0.);
+                // Inside the window...
+                assert!(rect.left >= 0. && rect.top >= 0.);
+                assert!(rect.left + rect.width <= window.width);
+                assert!(rect.top + rect.height <= window.height);
+                // ...and not on top of anything else
+                for b in &slots[i + 1..] {
+                    assert!(
+                        !overlaps(&rect, &layout.rect(*b)),
+                        "{:?} overlaps {:?} at {}x{}",
+                        a,
+                        b,
+                        width,
+                        height
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn light_placement() {
+        // Small boards keep the default placement
+        assert_eq!(light_scale(5, 5), 1.);
+        assert_eq!(light_position(5, 5), Vec3::new(3., 5., 3.));
+        assert_eq!(light_position(3, 4), Vec3::new(3., 5., 3.));
+        // Larger ones move the light away proportionally to their biggest side
+        assert_eq!(light_scale(15, 10), 3.);
+        assert_eq!(light_position(10, 15), Vec3::new(9., 15., 9.));
+    }
+
+    #[test]
+    fn hint_penalty() {
+        let mut timer = GameTimer {
+            timer: Timer::from_seconds(1., true),
+            ticks: 12,
+        };
+
+        timer.penalize(HINT_PENALTY);
+        assert_eq!(timer.ticks, 12 + HINT_PENALTY);
+    }
+}
@@ -0,0 +1,53 @@
+use minesweeper::game::*;
+
+#[test]
+fn from_cells() {
+    const H: u8 = 3;
+    const W: u8 = 4;
+
+    // x000
+    // 0000
+    // 000x
+    let mut cells = vec![GameCell::default(); (H * W) as usize];
+    cells[0].mine = true;
+    cells[(H * W) as usize - 1].mine = true;
+    cells[1].state = CellState::Flagged;
+
+    let mut game = Game::from_cells(W, H, cells).unwrap();
+    assert_eq!(game.width(), W);
+    assert_eq!(game.height(), H);
+    assert_eq!(game.state(), GameState::Continue);
+    assert_eq!(game.mines(), 2);
+    assert_eq!(game.flagged(), 1);
+    assert_eq!(game.has_mine(0, 0), Some(true));
+    assert_eq!(game.has_mine(W - 1, H - 1), Some(true));
+    assert_eq!(game.cell_state(1, 0), Some(CellState::Flagged));
+    assert_eq!(game.adjacent_mines(1, 1), Some(1));
+    assert_eq!(game.adjacent_mines(2, 1), Some(1));
+    assert_eq!(game.adjacent_mines(3, 0), Some(0));
+
+    assert!(game.open(3, 0));
+    assert_eq!(game.cell_state(3, 0), Some(CellState::Uncovered));
+
+    // The number of cells must match the dimensions
+    assert!(Game::from_cells(W, H, vec![GameCell::default(); 11]).is_none());
+    assert!(Game::from_cells(W, H, vec![]).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json() {
+    let mut cells = vec![GameCell::default(); 4 * 4];
+    cells[0].mine = true;
+    cells[15].mine = true;
+    let mut game = Game::from_cells(4, 4, cells).unwrap();
+    game.open(3, 0);
+    game.flag(0, 0);
+    game.flag(2, 3);
+
+    let json = game.to_json();
+    assert_eq!(Game::from_json(&json).unwrap(), game);
+
+    // Truncated payloads are errors, not panics
+    assert!(Game::from_json(&json[..json.len() / 2]).is_err());
+}